@@ -0,0 +1,327 @@
+use std::{
+    cell::Cell,
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
+};
+
+use tokio::sync::oneshot;
+
+use crate::resp::RespFrame;
+
+use super::{Backend, BackendError, BackendInner};
+
+/// Try to serve a blocked command from the given key. Returns None if the key
+/// can't serve it yet.
+pub type ServeFn =
+    Box<dyn Fn(&BackendInner, &str) -> Result<Option<RespFrame>, BackendError> + Send + Sync>;
+
+/// Clients blocked on keys, and the keys that may be able to serve them.
+#[derive(Default)]
+pub(crate) struct Blocking {
+    waiters: Mutex<Waiters>,
+    // keys written since the waiters were last served
+    ready: Mutex<VecDeque<String>>,
+    // number of parked clients, so writes don't take the lock when nobody waits
+    parked: AtomicUsize,
+}
+
+#[derive(Default)]
+struct Waiters {
+    next_id: u64,
+    // the clients blocked on each key, in the order they blocked
+    queues: HashMap<String, VecDeque<u64>>,
+    parked: HashMap<u64, Parked>,
+}
+
+struct Parked {
+    keys: Vec<String>,
+    serve: ServeFn,
+    reply: oneshot::Sender<RespFrame>,
+}
+
+/// The outcome of `Backend::block`.
+#[derive(Debug)]
+pub enum Blocked {
+    Ready(RespFrame),
+    Parked(Waiter),
+}
+
+/// A command parked until one of its keys serves it. Dropping it unblocks the command.
+#[derive(Debug)]
+pub struct Waiter {
+    id: u64,
+    reply: oneshot::Receiver<RespFrame>,
+    backend: Backend,
+}
+
+thread_local! {
+    // set while this thread holds the waiters, so that serving a client doesn't try
+    // to take them again when it writes to another key (BLMOVE)
+    static SERVING: Cell<bool> = const { Cell::new(false) };
+}
+
+impl Backend {
+    /// Try `serve` on each of `keys` in order. If none of them can serve it yet,
+    /// park it until a write makes one of them ready.
+    pub fn block(&self, keys: Vec<String>, serve: ServeFn) -> Result<Blocked, BackendError> {
+        // counted before the keys are tried: a write the keys miss then happens after
+        // the count went up, so the write is sure to signal the key
+        self.blocking.parked.fetch_add(1, Ordering::Relaxed);
+        let blocked = self.with_waiters(|waiters| {
+            for key in &keys {
+                if let Some(reply) = serve(self, key)? {
+                    return Ok(Blocked::Ready(reply));
+                }
+            }
+            let (tx, rx) = oneshot::channel();
+            let id = waiters.park(keys, serve, tx);
+            Ok(Blocked::Parked(Waiter {
+                id,
+                reply: rx,
+                backend: self.clone(),
+            }))
+        });
+        if !matches!(blocked, Ok(Blocked::Parked(_))) {
+            self.blocking.parked.fetch_sub(1, Ordering::Relaxed);
+        }
+        blocked
+    }
+}
+
+impl Waiter {
+    /// Wait until the command is served. Returns None once `timeout` expires,
+    /// without a timeout the command waits forever.
+    pub async fn wait(mut self, timeout: Option<Duration>) -> Option<RespFrame> {
+        let reply = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut self.reply).await.ok(),
+            None => Some((&mut self.reply).await),
+        };
+        if let Some(Ok(reply)) = reply {
+            return Some(reply);
+        }
+        // the command may have been served between the timeout and unparking it
+        self.unpark();
+        self.reply.try_recv().ok()
+    }
+
+    fn unpark(&self) {
+        let backend = &self.backend;
+        backend.with_waiters(|waiters| {
+            if waiters.unpark(self.id).is_some() {
+                backend.blocking.parked.fetch_sub(1, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.unpark();
+    }
+}
+
+impl BackendInner {
+    /// Serve the clients blocked on `key`. Must be called without holding the keyspace
+    /// lock, after every write that may make `key` able to serve a blocked client.
+    pub(crate) fn signal_ready(&self, key: &str) {
+        if self.blocking.parked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        lock(&self.blocking.ready).push_back(key.to_string());
+        // a thread already serving picks the key up before it lets the waiters go
+        if !SERVING.get() {
+            self.with_waiters(|_| {});
+        }
+    }
+
+    // run `f` while holding the waiters, then serve the clients blocked on the keys
+    // that became ready in the meantime
+    fn with_waiters<T>(&self, f: impl FnOnce(&mut Waiters) -> T) -> T {
+        let mut waiters = lock(&self.blocking.waiters);
+        let _serving = Serving(SERVING.replace(true));
+        let result = f(&mut waiters);
+        loop {
+            let Some(key) = lock(&self.blocking.ready).pop_front() else {
+                break;
+            };
+            let served = waiters.serve(self, &key);
+            self.blocking.parked.fetch_sub(served, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+// restores the previous value of SERVING, even if serving a client panics
+struct Serving(bool);
+
+impl Drop for Serving {
+    fn drop(&mut self) {
+        SERVING.set(self.0);
+    }
+}
+
+impl Waiters {
+    fn park(
+        &mut self,
+        keys: Vec<String>,
+        serve: ServeFn,
+        reply: oneshot::Sender<RespFrame>,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        for key in &keys {
+            self.queues.entry(key.clone()).or_default().push_back(id);
+        }
+        self.parked.insert(id, Parked { keys, serve, reply });
+        id
+    }
+
+    fn unpark(&mut self, id: u64) -> Option<Parked> {
+        let parked = self.parked.remove(&id)?;
+        for key in &parked.keys {
+            if let Some(queue) = self.queues.get_mut(key) {
+                queue.retain(|&other| other != id);
+                if queue.is_empty() {
+                    self.queues.remove(key);
+                }
+            }
+        }
+        Some(parked)
+    }
+
+    // serve the clients blocked on `key` in order, as long as the key can serve them.
+    // Returns the number of clients served.
+    fn serve(&mut self, backend: &BackendInner, key: &str) -> usize {
+        let Some(queue) = self.queues.get(key) else {
+            return 0;
+        };
+        let mut served = 0;
+        for id in queue.clone() {
+            let parked = &self.parked[&id];
+            if parked.reply.is_closed() {
+                continue;
+            }
+            // a client of the wrong type doesn't stop the ones behind it
+            let reply = match (parked.serve)(backend, key) {
+                Ok(Some(reply)) => reply,
+                Ok(None) | Err(_) => continue,
+            };
+            if let Some(parked) = self.unpark(id) {
+                let _ = parked.reply.send(reply);
+                served += 1;
+            }
+        }
+        served
+    }
+}
+
+impl fmt::Debug for Blocking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocking")
+            .field("parked", &self.parked.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ListEnd, test_util::bulk};
+
+    fn pop() -> ServeFn {
+        Box::new(|backend, key| {
+            Ok(backend
+                .pop(key, ListEnd::Left, 1)?
+                .and_then(|mut values| values.pop()))
+        })
+    }
+
+    fn park(backend: &Backend, keys: &[&str], serve: ServeFn) -> Waiter {
+        let keys = keys.iter().map(|k| k.to_string()).collect();
+        match backend.block(keys, serve) {
+            Ok(Blocked::Parked(waiter)) => waiter,
+            other => panic!("expected the command to block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_block_serves_waiters_in_order() -> anyhow::Result<()> {
+        let backend = Backend::default();
+        let mut first = park(&backend, &["list"], pop());
+        let mut second = park(&backend, &["other", "list"], pop());
+        let mut third = park(&backend, &["list"], pop());
+
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            vec![bulk("a"), bulk("b")],
+        )?;
+        assert_eq!(first.reply.try_recv()?, bulk("a"));
+        assert_eq!(second.reply.try_recv()?, bulk("b"));
+        assert!(third.reply.try_recv().is_err());
+        assert!(backend.db.is_empty());
+
+        // a dropped waiter doesn't take elements anymore
+        drop(third);
+        assert_eq!(backend.blocking.parked.load(Ordering::Relaxed), 0);
+        backend.push("list".to_string(), ListEnd::Right, vec![bulk("c")])?;
+        assert_eq!(backend.llen("list")?, 1);
+
+        // a key that can serve the command right away doesn't block it
+        assert!(matches!(
+            backend.block(vec!["list".to_string()], pop()),
+            Ok(Blocked::Ready(value)) if value == bulk("c")
+        ));
+        backend.set("string".to_string(), bulk("v"));
+        assert!(matches!(
+            backend.block(vec!["string".to_string()], pop()),
+            Err(BackendError::WrongType)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_serves_moved_elements() -> anyhow::Result<()> {
+        let backend = Backend::default();
+        let mut mover = park(
+            &backend,
+            &["source"],
+            Box::new(|backend, key| {
+                backend.lmove(key, "destination", ListEnd::Left, ListEnd::Right)
+            }),
+        );
+        let mut popper = park(&backend, &["destination"], pop());
+
+        backend.push("source".to_string(), ListEnd::Left, vec![bulk("a")])?;
+        assert_eq!(mover.reply.try_recv()?, bulk("a"));
+        assert_eq!(popper.reply.try_recv()?, bulk("a"));
+        assert!(backend.db.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_waiter_times_out() -> anyhow::Result<()> {
+        let backend = Backend::default();
+        let waiter = park(&backend, &["list"], pop());
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(waiter.wait(timeout).await, None);
+        assert_eq!(backend.blocking.parked.load(Ordering::Relaxed), 0);
+
+        let waiter = park(&backend, &["list"], pop());
+        let pusher = backend.clone();
+        tokio::spawn(
+            async move { pusher.push("list".to_string(), ListEnd::Left, vec![bulk("a")]) },
+        );
+        assert_eq!(waiter.wait(None).await, Some(bulk("a")));
+        Ok(())
+    }
+}
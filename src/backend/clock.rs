@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time used for key expiration.
+pub trait Clock: Debug + Send + Sync {
+    /// unix time in milliseconds
+    fn now_ms(&self) -> i64;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+pub(crate) use mock::MockClock;
+
+#[cfg(test)]
+mod mock {
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    use super::Clock;
+
+    /// A clock that only moves when told to, for testing expiration.
+    #[derive(Debug)]
+    pub struct MockClock(AtomicI64);
+
+    impl MockClock {
+        pub fn new(now_ms: i64) -> Self {
+            Self(AtomicI64::new(now_ms))
+        }
+
+        pub fn advance(&self, ms: i64) {
+            self.0.fetch_add(ms, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_ms(&self) -> i64 {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::HashMap,
+    sync::{MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use rand::seq::index;
+use tracing::debug;
+
+use super::{Backend, BackendInner};
+
+// same knobs as redis' activeExpireCycle
+const ACTIVE_EXPIRE_CYCLE_HZ: u64 = 10;
+const ACTIVE_EXPIRE_CYCLE_KEYS_PER_LOOP: usize = 20;
+// keep sampling while more than this percentage of the sampled keys were expired
+const ACTIVE_EXPIRE_CYCLE_ACCEPTABLE_STALE: usize = 10;
+// percentage of each tick the cycle may use
+const ACTIVE_EXPIRE_CYCLE_SLOW_TIME_PERC: u64 = 25;
+
+/// The keys that were given a deadline, so that the active expire cycle can sample
+/// them without walking the keyspace. A key stays in the index until a sample finds
+/// it gone or without a deadline, which keeps the index right without every command
+/// that deletes a key or clears its deadline having to update it.
+#[derive(Debug, Default)]
+pub(crate) struct VolatileKeys {
+    keys: Vec<String>,
+    positions: HashMap<String, usize>,
+}
+
+/// Periodically reap expired keys that are never accessed again.
+pub async fn run_active_expire(backend: Backend) {
+    let period = Duration::from_millis(1000 / ACTIVE_EXPIRE_CYCLE_HZ);
+    let budget = period * ACTIVE_EXPIRE_CYCLE_SLOW_TIME_PERC as u32 / 100;
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        let expired = backend.active_expire_cycle(budget);
+        if expired > 0 {
+            debug!("Active expire cycle removed {} keys", expired);
+        }
+    }
+}
+
+impl BackendInner {
+    /// Sample keys with a deadline and delete the expired ones, repeating while
+    /// the sample shows too many stale keys and `budget` is not used up.
+    /// Returns the number of deleted keys.
+    pub fn active_expire_cycle(&self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut total = 0;
+        loop {
+            let guard = self.shared();
+            let (sampled, expired, dropped) = self.expire_sample(self.now_ms());
+            drop(guard);
+            total += expired;
+
+            // keys dropped from the index count as stale so that they are drained too
+            if sampled == 0
+                || (expired + dropped) * 100 <= sampled * ACTIVE_EXPIRE_CYCLE_ACCEPTABLE_STALE
+                || start.elapsed() >= budget
+            {
+                return total;
+            }
+        }
+    }
+
+    /// Add `key` to the keys the active expire cycle samples. Must not be called
+    /// while holding a reference into the keyspace, see `expire_sample`.
+    pub(crate) fn track_deadline(&self, key: &str) {
+        self.volatile_keys().insert(key);
+    }
+
+    pub(crate) fn volatile_keys(&self) -> MutexGuard<'_, VolatileKeys> {
+        self.volatile.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // returns (sampled, expired, dropped from the index). The index stays locked while
+    // the sampled keys are checked, so a deadline set meanwhile is tracked again
+    // only after this sample is done with the key.
+    fn expire_sample(&self, now: i64) -> (usize, usize, usize) {
+        let mut volatile = self.volatile_keys();
+        let sample = volatile.sample(ACTIVE_EXPIRE_CYCLE_KEYS_PER_LOOP);
+        let (mut expired, mut dropped) = (0, 0);
+        for key in &sample {
+            match self.db.get(key) {
+                Some(entry) if entry.is_expired(now) => {
+                    drop(entry);
+                    if self.db.remove_if(key, |_, v| v.is_expired(now)).is_some() {
+                        expired += 1;
+                    }
+                }
+                Some(entry) if entry.has_deadline() => continue,
+                _ => dropped += 1,
+            }
+            volatile.remove(key);
+        }
+        (sample.len(), expired, dropped)
+    }
+}
+
+impl VolatileKeys {
+    fn insert(&mut self, key: &str) {
+        if !self.positions.contains_key(key) {
+            self.positions.insert(key.to_string(), self.keys.len());
+            self.keys.push(key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        let Some(position) = self.positions.remove(key) else {
+            return;
+        };
+        self.keys.swap_remove(position);
+        if let Some(moved) = self.keys.get(position) {
+            self.positions.insert(moved.clone(), position);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+        self.positions.clear();
+    }
+
+    // up to `count` distinct keys picked at random
+    fn sample(&self, count: usize) -> Vec<String> {
+        let count = count.min(self.keys.len());
+        index::sample(&mut rand::thread_rng(), self.keys.len(), count)
+            .into_iter()
+            .map(|i| self.keys[i].clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        backend::{ExpireCondition, MockClock},
+        resp::{BulkString, RespFrame},
+    };
+
+    #[test]
+    fn test_active_expire_cycle() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        for i in 0..100 {
+            let key = format!("key{}", i);
+            backend.set(key.clone(), RespFrame::BulkString(BulkString("v".into())));
+            if i % 2 == 0 {
+                backend.expire(&key, 1_500, ExpireCondition::default());
+            }
+        }
+        backend
+            .hset(
+                "hash".to_string(),
+                "field".to_string(),
+                RespFrame::BulkString(BulkString("v".into())),
+            )
+            .unwrap();
+        backend.expire("hash", 1_500, ExpireCondition::default());
+
+        assert_eq!(backend.active_expire_cycle(Duration::from_secs(10)), 0);
+        assert_eq!(backend.db.len(), 101);
+        assert_eq!(backend.volatile_keys().keys.len(), 51);
+
+        clock.advance(500);
+        assert_eq!(backend.active_expire_cycle(Duration::from_secs(10)), 51);
+        assert_eq!(backend.db.len(), 50);
+        assert!(backend.key_type("hash").is_none());
+    }
+
+    #[test]
+    fn test_volatile_keys_are_dropped_lazily() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let value = || RespFrame::BulkString(BulkString("v".into()));
+        for key in ["a", "b", "c", "d"] {
+            backend.set(key.to_string(), value());
+            backend.expire(key, 5_000, ExpireCondition::default());
+        }
+        backend.set("plain".to_string(), value());
+        backend.persist("a");
+        backend.del(&["b".to_string()]);
+        backend.rename("c", "e".to_string(), false);
+        assert_eq!(backend.volatile_keys().keys.len(), 5);
+
+        // a, b and c are stale, the sample drains them without expiring anything
+        assert_eq!(backend.active_expire_cycle(Duration::from_secs(10)), 0);
+        let mut keys = backend.volatile_keys().keys.clone();
+        keys.sort();
+        assert_eq!(keys, ["d", "e"]);
+
+        clock.advance(4_000);
+        assert_eq!(backend.active_expire_cycle(Duration::from_secs(10)), 2);
+        assert!(backend.volatile_keys().keys.is_empty());
+        assert_eq!(backend.db.len(), 2);
+    }
+
+    #[test]
+    fn test_hash_with_all_fields_expired() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let value = || RespFrame::BulkString(BulkString("v".into()));
+        for key in ["h1", "h2", "h3"] {
+            backend
+                .hset(key.to_string(), "f".to_string(), value())
+                .unwrap();
+            let fields = ["f".to_string()];
+            backend
+                .hexpire(key, &fields, 1_050, ExpireCondition::default())
+                .unwrap();
+        }
+        backend
+            .hset("h2".to_string(), "g".to_string(), value())
+            .unwrap();
+
+        clock.advance(500);
+        assert_eq!(backend.dbsize(), 3);
+        // h1 is gone for every command even before anything reaps it
+        assert_eq!(backend.exists(&["h1".to_string()]), 0);
+        assert_eq!(backend.key_type("h1"), None);
+        assert_eq!(backend.expire_at("h1"), None);
+        assert_eq!(backend.key_type("h2"), Some("hash"));
+        assert_eq!(backend.dbsize(), 2);
+
+        // h3 is reaped without being looked up
+        assert_eq!(backend.active_expire_cycle(Duration::from_secs(10)), 1);
+        assert_eq!(backend.dbsize(), 1);
+        assert_eq!(backend.hlen("h2"), Ok(1));
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::BuildHasher,
+};
+
+use dashmap::mapref::entry::Entry;
+use rand::seq::{IteratorRandom, SliceRandom};
+
+use crate::resp::{encode::format_double, BulkString, RespFrame};
+
+use super::{
+    glob_match,
+    scan::scan_bucket,
+    string::{parse_float, parse_integer, string_bytes},
+    BackendError, BackendInner, ExpireCondition, Expiring, SetTtl, Value,
+};
+
+/// The fields of a hash, some of them with their own deadline (unix time in ms).
+/// Fields past their deadline are reaped by the next hash command on the key,
+/// and a hash whose fields have all expired is treated as an expired key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hash {
+    // a hashbrown map so that HSCAN can walk its buckets
+    values: hashbrown::HashMap<String, RespFrame, RandomState>,
+    deadlines: HashMap<String, i64>,
+    // no field expires before this, so most accesses don't have to look at the deadlines
+    next_expire_at: Option<i64>,
+}
+
+impl Hash {
+    pub fn get(&self, field: &str) -> Option<&RespFrame> {
+        self.values.get(field)
+    }
+
+    pub fn contains_key(&self, field: &str) -> bool {
+        self.values.contains_key(field)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &RespFrame)> {
+        self.values.iter()
+    }
+
+    /// Set `field` to `value`, removing any deadline the field had.
+    pub fn insert(&mut self, field: String, value: RespFrame) -> Option<RespFrame> {
+        self.deadlines.remove(&field);
+        self.values.insert(field, value)
+    }
+
+    pub fn remove(&mut self, field: &str) -> Option<RespFrame> {
+        self.deadlines.remove(field);
+        self.values.remove(field)
+    }
+
+    pub fn expire_at(&self, field: &str) -> Option<i64> {
+        self.deadlines.get(field).copied()
+    }
+
+    fn set_expire_at(&mut self, field: &str, at: i64) {
+        self.deadlines.insert(field.to_string(), at);
+        self.next_expire_at = Some(self.next_expire_at.map_or(at, |next| next.min(at)));
+    }
+
+    fn persist(&mut self, field: &str) -> bool {
+        self.deadlines.remove(field).is_some()
+    }
+
+    /// One step of an HSCAN iteration starting at `cursor`, see `scan_bucket`.
+    /// Returns the next cursor, 0 when the iteration is complete, and the fields
+    /// of the buckets visited, at least `count` of them unless the iteration ends.
+    pub fn scan(&self, mut cursor: u64, count: usize) -> (u64, Vec<(&String, &RespFrame)>) {
+        let mut page = vec![];
+        // like redis, give up on filling the page after visiting many empty buckets
+        let mut budget = count.saturating_mul(10);
+        loop {
+            cursor = scan_bucket(
+                self.values.raw_table(),
+                cursor,
+                |(field, _)| self.values.hasher().hash_one(field),
+                |(field, value)| page.push((field, value)),
+            );
+            budget -= 1;
+            if cursor == 0 || page.len() >= count || budget == 0 {
+                return (cursor, page);
+            }
+        }
+    }
+
+    pub(super) fn has_deadlines(&self) -> bool {
+        !self.deadlines.is_empty()
+    }
+
+    // every field is past its deadline, so the hash is as good as deleted
+    pub(super) fn all_fields_expired(&self, now: i64) -> bool {
+        self.has_expired_fields(now)
+            && self.deadlines.len() == self.values.len()
+            && self.deadlines.values().all(|at| *at <= now)
+    }
+
+    fn has_expired_fields(&self, now: i64) -> bool {
+        self.next_expire_at.is_some_and(|at| at <= now)
+    }
+
+    fn remove_expired_fields(&mut self, now: i64) {
+        if !self.has_expired_fields(now) {
+            return;
+        }
+        let values = &mut self.values;
+        self.deadlines.retain(|field, at| {
+            if *at > now {
+                return true;
+            }
+            values.remove(field);
+            false
+        });
+        self.next_expire_at = self.deadlines.values().min().copied();
+    }
+}
+
+impl BackendInner {
+    pub fn hget(&self, key: &str, field: &str) -> Result<Option<RespFrame>, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| hash.get(field).cloned())?
+            .flatten())
+    }
+
+    pub fn hset(&self, key: String, field: String, value: RespFrame) -> Result<(), BackendError> {
+        self.hmset(key, vec![(field, value)]).map(|_| ())
+    }
+
+    pub fn hgetall(&self, key: &str) -> Result<Option<HashMap<String, RespFrame>>, BackendError> {
+        self.read_hash(key, |hash| {
+            hash.iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect()
+        })
+    }
+
+    /// Set all the field/value pairs. Returns the number of fields that were created.
+    pub fn hmset(
+        &self,
+        key: String,
+        pairs: Vec<(String, RespFrame)>,
+    ) -> Result<usize, BackendError> {
+        self.write_hash(key, true, |hash| {
+            let mut created = 0;
+            for (field, value) in pairs {
+                if hash.insert(field, value).is_none() {
+                    created += 1;
+                }
+            }
+            created
+        })
+        .map(Option::unwrap_or_default)
+    }
+
+    /// Set `field` only if it doesn't exist yet. Returns whether it was set.
+    pub fn hsetnx(
+        &self,
+        key: String,
+        field: String,
+        value: RespFrame,
+    ) -> Result<bool, BackendError> {
+        self.write_hash(key, true, |hash| {
+            if hash.contains_key(&field) {
+                return false;
+            }
+            hash.insert(field, value);
+            true
+        })
+        .map(Option::unwrap_or_default)
+    }
+
+    /// Delete the given fields, the hash is deleted with its last field.
+    /// Returns the number of fields deleted.
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, BackendError> {
+        self.write_hash(key.to_string(), false, |hash| {
+            fields
+                .iter()
+                .filter(|field| hash.remove(field.as_str()).is_some())
+                .count()
+        })
+        .map(Option::unwrap_or_default)
+    }
+
+    pub fn hexists(&self, key: &str, field: &str) -> Result<bool, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| hash.contains_key(field))?
+            .unwrap_or_default())
+    }
+
+    pub fn hlen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.read_hash(key, |hash| hash.len())?.unwrap_or_default())
+    }
+
+    pub fn hkeys(&self, key: &str) -> Result<Vec<String>, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| hash.values.keys().cloned().collect())?
+            .unwrap_or_default())
+    }
+
+    pub fn hvals(&self, key: &str) -> Result<Vec<RespFrame>, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| hash.values.values().cloned().collect())?
+            .unwrap_or_default())
+    }
+
+    pub fn hmget(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<RespFrame>>, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| {
+                fields
+                    .iter()
+                    .map(|field| hash.get(field).cloned())
+                    .collect()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]))
+    }
+
+    /// Length of the value of `field`, 0 if it doesn't exist.
+    pub fn hstrlen(&self, key: &str, field: &str) -> Result<usize, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| hash.get(field).map(|v| string_bytes(v).len()))?
+            .flatten()
+            .unwrap_or_default())
+    }
+
+    /// Atomically add `delta` to the integer in `field`, a missing field counts as 0.
+    pub fn hincr_by(&self, key: String, field: String, delta: i64) -> Result<i64, BackendError> {
+        self.write_hash(key, true, |hash| {
+            let current = match hash.get(&field) {
+                Some(current) => {
+                    parse_integer(&string_bytes(current)).ok_or(BackendError::HashNotInteger)?
+                }
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+            // unlike HSET, incrementing a field keeps its deadline
+            hash.values
+                .insert(field, BulkString(value.to_string().into_bytes()).into());
+            Ok(value)
+        })?
+        .unwrap_or(Ok(delta))
+    }
+
+    /// Atomically add `delta` to the float in `field`, a missing field counts as 0.
+    pub fn hincr_by_float(
+        &self,
+        key: String,
+        field: String,
+        delta: f64,
+    ) -> Result<f64, BackendError> {
+        self.write_hash(key, true, |hash| {
+            let current = match hash.get(&field) {
+                Some(current) => {
+                    parse_float(&string_bytes(current)).ok_or(BackendError::HashNotFloat)?
+                }
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(BackendError::NanOrInfinity);
+            }
+            hash.values
+                .insert(field, BulkString(format_double(value).into_bytes()).into());
+            Ok(value)
+        })?
+        .unwrap_or(Ok(delta))
+    }
+
+    /// Random fields with their values. With a positive `count` the fields are
+    /// distinct, with a negative one `-count` fields are returned and may repeat.
+    pub fn hrandfield(
+        &self,
+        key: &str,
+        count: i64,
+    ) -> Result<Vec<(String, RespFrame)>, BackendError> {
+        let mut rng = rand::thread_rng();
+        Ok(self
+            .read_hash(key, |hash| {
+                if count >= 0 {
+                    // only the chosen pairs are copied
+                    let count = usize::try_from(count).unwrap_or(usize::MAX).min(hash.len());
+                    let chosen = hash.iter().choose_multiple(&mut rng, count);
+                    return chosen
+                        .into_iter()
+                        .map(|(f, v)| (f.clone(), v.clone()))
+                        .collect();
+                }
+                let pairs: Vec<_> = hash.iter().collect();
+                (0..count.unsigned_abs())
+                    .filter_map(|_| pairs.choose(&mut rng))
+                    .map(|(f, v)| (f.to_string(), (*v).clone()))
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// One step of an HSCAN iteration starting at `cursor`, see `Hash::scan`.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&str>,
+    ) -> Result<(u64, Vec<(String, RespFrame)>), BackendError> {
+        Ok(self
+            .read_hash(key, |hash| {
+                let (next, page) = hash.scan(cursor, count);
+                let pairs = page
+                    .into_iter()
+                    .filter(|(field, _)| {
+                        pattern.is_none_or(|p| glob_match(p.as_bytes(), field.as_bytes()))
+                    })
+                    .map(|(field, value)| (field.clone(), value.clone()))
+                    .collect();
+                (next, pairs)
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Set the deadline of the given fields to `at` (unix time in ms) where `condition`
+    /// allows it, a deadline in the past deletes the field. For each field, returns
+    /// None if it doesn't exist, otherwise whether its deadline was set, or whether it
+    /// was deleted if `at` is in the past.
+    pub fn hexpire(
+        &self,
+        key: &str,
+        fields: &[String],
+        at: i64,
+        condition: ExpireCondition,
+    ) -> Result<Vec<Option<bool>>, BackendError> {
+        let now = self.now_ms();
+        let results = self
+            .write_hash(key.to_string(), false, |hash| {
+                fields
+                    .iter()
+                    .map(|field| {
+                        if !hash.contains_key(field) {
+                            return None;
+                        }
+                        if !condition.allows(hash.expire_at(field), at) {
+                            return Some(false);
+                        }
+                        if at <= now {
+                            hash.remove(field);
+                        } else {
+                            hash.set_expire_at(field, at);
+                        }
+                        Some(true)
+                    })
+                    .collect::<Vec<_>>()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]);
+        if at > now && results.contains(&Some(true)) {
+            self.track_deadline(key);
+        }
+        Ok(results)
+    }
+
+    /// The deadline of each field: None if the field doesn't exist, Some(None) if
+    /// it has no deadline.
+    pub fn hexpire_at(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<Option<i64>>>, BackendError> {
+        Ok(self
+            .read_hash(key, |hash| {
+                fields
+                    .iter()
+                    .map(|field| hash.contains_key(field).then(|| hash.expire_at(field)))
+                    .collect()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]))
+    }
+
+    /// Remove the deadline of each field. Returns None for a field that doesn't
+    /// exist, otherwise whether it had a deadline.
+    pub fn hpersist(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<bool>>, BackendError> {
+        Ok(self
+            .write_hash(key.to_string(), false, |hash| {
+                fields
+                    .iter()
+                    .map(|field| hash.contains_key(field).then(|| hash.persist(field)))
+                    .collect()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]))
+    }
+
+    /// Get the values of the given fields and update the deadline of the existing
+    /// ones, see `BackendInner::getex`.
+    pub fn hgetex(
+        &self,
+        key: &str,
+        fields: &[String],
+        ttl: SetTtl,
+    ) -> Result<Vec<Option<RespFrame>>, BackendError> {
+        let now = self.now_ms();
+        let values = self
+            .write_hash(key.to_string(), false, |hash| {
+                fields
+                    .iter()
+                    .map(|field| {
+                        let value = hash.get(field).cloned()?;
+                        match ttl {
+                            SetTtl::Keep => {}
+                            SetTtl::Clear => {
+                                hash.persist(field);
+                            }
+                            SetTtl::At(at) if at <= now => {
+                                hash.remove(field);
+                            }
+                            SetTtl::At(at) => hash.set_expire_at(field, at),
+                        }
+                        Some(value)
+                    })
+                    .collect::<Vec<_>>()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]);
+        if matches!(ttl, SetTtl::At(at) if at > now) && values.iter().any(Option::is_some) {
+            self.track_deadline(key);
+        }
+        Ok(values)
+    }
+
+    // run `f` on the hash at `key`, None if the key doesn't exist
+    fn read_hash<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&Hash) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        {
+            let _guard = self.shared();
+            let now = self.now_ms();
+            match self.lookup(key).as_deref() {
+                Some(Expiring {
+                    value: Value::Hash(hash),
+                    ..
+                }) if !hash.has_expired_fields(now) => return Ok(Some(f(hash))),
+                Some(Expiring {
+                    value: Value::Hash(_),
+                    ..
+                }) => {}
+                Some(_) => return Err(BackendError::WrongType),
+                None => return Ok(None),
+            }
+        }
+        // some fields are past their deadline, reap them before reading
+        self.write_hash(key.to_string(), false, |hash| f(hash))
+    }
+
+    // run `f` on the hash at `key` while holding the lock of its shard, after reaping
+    // its expired fields. A missing key is created as an empty hash if `create` is set,
+    // otherwise `f` is not run and None is returned. A hash left empty is deleted.
+    fn write_hash<T>(
+        &self,
+        key: String,
+        create: bool,
+        f: impl FnOnce(&mut Hash) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        let _guard = self.shared();
+        let mut entry = match self.entry(key) {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) if create => {
+                entry.insert_entry(Expiring::new(Value::Hash(Hash::default())))
+            }
+            Entry::Vacant(_) => return Ok(None),
+        };
+        let Value::Hash(hash) = &mut entry.get_mut().value else {
+            return Err(BackendError::WrongType);
+        };
+        hash.remove_expired_fields(self.now_ms());
+        if hash.is_empty() && !create {
+            entry.remove();
+            return Ok(None);
+        }
+        let result = f(hash);
+        if hash.is_empty() {
+            entry.remove();
+        }
+        Ok(Some(result))
+    }
+}
@@ -0,0 +1,135 @@
+use rand::seq::IteratorRandom;
+
+use std::hash::BuildHasher;
+
+use super::{glob_match, scan::scan_bucket, BackendInner};
+
+impl BackendInner {
+    /// Delete the given keys atomically. Returns the number of keys deleted.
+    pub fn del(&self, keys: &[String]) -> usize {
+        let _guard = self.exclusive();
+        let now = self.now_ms();
+        keys.iter()
+            .filter(|key| {
+                self.db
+                    .remove(key.as_str())
+                    .is_some_and(|(_, v)| !v.is_expired(now))
+            })
+            .count()
+    }
+
+    /// Count the given keys that exist, a key mentioned twice is counted twice.
+    pub fn exists(&self, keys: &[String]) -> usize {
+        let _guard = self.shared();
+        keys.iter().filter(|key| self.lookup(key).is_some()).count()
+    }
+
+    /// All the keys matching the glob-style `pattern`.
+    pub fn keys(&self, pattern: &str) -> Vec<String> {
+        let _guard = self.shared();
+        let now = self.now_ms();
+        self.db
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .filter(|entry| glob_match(pattern.as_bytes(), entry.key().as_bytes()))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// One step of a SCAN iteration starting at `cursor`. Returns the next cursor,
+    /// 0 when the iteration is complete, and the keys of this step that match
+    /// `pattern` and `type_name`. The low bits of a cursor select a shard of the
+    /// keyspace and the others a bucket in it, see `scan_bucket`.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&str>,
+        type_name: Option<&str>,
+    ) -> (u64, Vec<String>) {
+        let _guard = self.shared();
+        let now = self.now_ms();
+        let shards = self.db.shards();
+        let shard_bits = shards.len().trailing_zeros();
+        let mut shard = (cursor & (shards.len() as u64 - 1)) as usize;
+        let mut bucket = cursor >> shard_bits;
+        let mut page = vec![];
+        // like redis, give up on filling the page after visiting many empty buckets
+        let mut budget = count.saturating_mul(10);
+        let next = loop {
+            bucket = scan_bucket(
+                &shards[shard].read(),
+                bucket,
+                |(key, _)| self.db.hasher().hash_one(key),
+                |(key, value)| {
+                    let value = value.get();
+                    if !value.is_expired(now) {
+                        page.push((key.clone(), value.value.type_name()));
+                    }
+                },
+            );
+            if bucket == 0 {
+                shard += 1;
+                if shard == shards.len() {
+                    break 0;
+                }
+            }
+            budget -= 1;
+            if page.len() >= count || budget == 0 {
+                break bucket << shard_bits | shard as u64;
+            }
+        };
+        let keys = page
+            .into_iter()
+            .filter(|(key, _)| pattern.is_none_or(|p| glob_match(p.as_bytes(), key.as_bytes())))
+            .filter(|(_, t)| type_name.is_none_or(|name| name.eq_ignore_ascii_case(t)))
+            .map(|(key, _)| key)
+            .collect();
+        (next, keys)
+    }
+
+    /// Move `key` and its deadline to `new_key`. With `nx`, an existing `new_key` is kept.
+    /// Returns None if `key` doesn't exist, otherwise whether the key was renamed.
+    pub fn rename(&self, key: &str, new_key: String, nx: bool) -> Option<bool> {
+        {
+            let _guard = self.exclusive();
+            self.lookup(key)?;
+            if nx && self.lookup(&new_key).is_some() {
+                return Some(false);
+            }
+            if key != new_key {
+                let (_, value) = self.db.remove(key)?;
+                let volatile = value.has_deadline();
+                self.db.insert(new_key.clone(), value);
+                if volatile {
+                    self.track_deadline(&new_key);
+                }
+            }
+        }
+        // the renamed key may be a list or a sorted set a client is blocked on
+        self.signal_ready(&new_key);
+        Some(true)
+    }
+
+    pub fn random_key(&self) -> Option<String> {
+        let _guard = self.shared();
+        let now = self.now_ms();
+        self.db
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .choose(&mut rand::thread_rng())
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Number of keys, including expired keys not reaped yet like redis does.
+    pub fn dbsize(&self) -> usize {
+        let _guard = self.shared();
+        self.db.len()
+    }
+
+    pub fn flush(&self) {
+        let _guard = self.exclusive();
+        self.db.clear();
+        self.volatile_keys().clear();
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::VecDeque;
+
+use crate::resp::RespFrame;
+
+use super::{string::string_bytes, BackendError, BackendInner, Collection, Expiring, Value};
+
+type List = VecDeque<RespFrame>;
+
+/// The end of a list an element is pushed to or popped from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+impl BackendInner {
+    /// Push `values` one after the other to `end` of the list. Returns the new length.
+    pub fn push(
+        &self,
+        key: String,
+        end: ListEnd,
+        values: Vec<RespFrame>,
+    ) -> Result<usize, BackendError> {
+        let len = self
+            .write_value(key.clone(), true, |list: &mut List| {
+                for value in values {
+                    push(list, end, value);
+                }
+                list.len()
+            })?
+            .unwrap_or_default();
+        self.signal_ready(&key);
+        Ok(len)
+    }
+
+    /// Pop up to `count` elements from `end` of the list, None if the key doesn't exist.
+    pub fn pop(
+        &self,
+        key: &str,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<RespFrame>>, BackendError> {
+        self.write_value(key.to_string(), false, |list: &mut List| {
+            pop(list, end, count)
+        })
+    }
+
+    /// Pop up to `count` elements from `end` of the first of `keys` holding a list.
+    /// Returns the key popped from and the elements.
+    pub fn mpop(
+        &self,
+        keys: &[String],
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<(String, Vec<RespFrame>)>, BackendError> {
+        let _guard = self.exclusive();
+        for key in keys {
+            if let Some(values) =
+                self.update_value(key.clone(), false, |list: &mut List| pop(list, end, count))?
+            {
+                return Ok(Some((key.clone(), values)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Atomically pop an element from `from` of the list at `source` and push it to `to`
+    /// of the list at `destination`. Returns None if `source` doesn't exist.
+    pub fn lmove(
+        &self,
+        source: &str,
+        destination: &str,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        let value = {
+            let _guard = self.exclusive();
+            // like redis, a destination of the wrong type fails before anything is popped
+            if let Some(entry) = self.lookup(destination) {
+                if !matches!(entry.value, Value::List(_)) {
+                    return Err(BackendError::WrongType);
+                }
+            }
+            let Some(mut entry) = self.lookup_mut(source) else {
+                return Ok(None);
+            };
+            let Value::List(list) = &mut entry.value else {
+                return Err(BackendError::WrongType);
+            };
+            let value = match from {
+                ListEnd::Left => list.pop_front(),
+                ListEnd::Right => list.pop_back(),
+            };
+            drop(entry);
+            let Some(value) = value else {
+                return Ok(None);
+            };
+
+            let mut entry = self
+                .entry(destination.to_string())
+                .or_insert_with(|| Expiring::new(Value::List(VecDeque::new())));
+            if let Value::List(list) = &mut entry.value {
+                push(list, to, value.clone());
+            }
+            drop(entry);
+            // removed last, so that rotating a list of one element keeps the list
+            self.db.remove_if(
+                source,
+                |_, v| matches!(&v.value, Value::List(l) if l.is_empty()),
+            );
+            value
+        };
+        self.signal_ready(destination);
+        Ok(Some(value))
+    }
+
+    /// The elements between `start` and `stop` (both inclusive),
+    /// negative indexes count from the end of the list.
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<RespFrame>, BackendError> {
+        Ok(self
+            .read_value(key, |list: &List| {
+                match list_range(list.len(), start, stop) {
+                    Some((start, stop)) => list.range(start..=stop).cloned().collect(),
+                    None => vec![],
+                }
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Indexes of the elements equal to `value`, starting from the `rank`-th match
+    /// (from the tail if `rank` is negative). At most `count` indexes are returned,
+    /// all of them if `count` is 0, and only the first `maxlen` elements scanned
+    /// (0 scans the whole list).
+    pub fn lpos(
+        &self,
+        key: &str,
+        value: &RespFrame,
+        rank: i64,
+        count: usize,
+        maxlen: usize,
+    ) -> Result<Vec<usize>, BackendError> {
+        let value = string_bytes(value);
+        let count = if count == 0 { usize::MAX } else { count };
+        let maxlen = if maxlen == 0 { usize::MAX } else { maxlen };
+        let skip = rank
+            .unsigned_abs()
+            .saturating_sub(1)
+            .try_into()
+            .unwrap_or(usize::MAX);
+        Ok(self
+            .read_value(key, |list: &List| {
+                let indexes: Box<dyn Iterator<Item = usize>> = if rank < 0 {
+                    Box::new((0..list.len()).rev())
+                } else {
+                    Box::new(0..list.len())
+                };
+                indexes
+                    .take(maxlen)
+                    .filter(|&i| string_bytes(&list[i]) == value)
+                    .skip(skip)
+                    .take(count)
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    pub fn llen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self
+            .read_value(key, |list: &List| list.len())?
+            .unwrap_or_default())
+    }
+
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<RespFrame>, BackendError> {
+        Ok(self
+            .read_value(key, |list: &List| {
+                list_index(list.len(), index).map(|index| list[index].clone())
+            })?
+            .flatten())
+    }
+
+    pub fn lset(&self, key: &str, index: i64, value: RespFrame) -> Result<(), BackendError> {
+        self.write_value(key.to_string(), false, |list: &mut List| {
+            let index = list_index(list.len(), index).ok_or(BackendError::IndexOutOfRange)?;
+            list[index] = value;
+            Ok(())
+        })?
+        .unwrap_or(Err(BackendError::NoSuchKey))
+    }
+
+    /// Insert `value` next to the first element equal to `pivot`. Returns the new
+    /// length, None if `pivot` was not found and 0 if the key doesn't exist.
+    pub fn linsert(
+        &self,
+        key: &str,
+        before: bool,
+        pivot: &RespFrame,
+        value: RespFrame,
+    ) -> Result<Option<usize>, BackendError> {
+        let pivot = string_bytes(pivot);
+        Ok(self
+            .write_value(key.to_string(), false, |list: &mut List| {
+                let index = list.iter().position(|e| string_bytes(e) == pivot)?;
+                list.insert(if before { index } else { index + 1 }, value);
+                Some(list.len())
+            })?
+            .unwrap_or(Some(0)))
+    }
+
+    /// Remove the elements equal to `value`: the first `count` ones if `count` is
+    /// positive, the last `-count` ones if it's negative, all of them if it's 0.
+    /// Returns the number of elements removed.
+    pub fn lrem(&self, key: &str, count: i64, value: &RespFrame) -> Result<usize, BackendError> {
+        let value = string_bytes(value);
+        let limit = match count {
+            0 => usize::MAX,
+            count => count.unsigned_abs().try_into().unwrap_or(usize::MAX),
+        };
+        Ok(self
+            .write_value(key.to_string(), false, |list: &mut List| {
+                // removing from the tail is removing from the head of the reversed list
+                if count < 0 {
+                    list.make_contiguous().reverse();
+                }
+                let mut removed = 0;
+                list.retain(|element| {
+                    if removed < limit && string_bytes(element) == value {
+                        removed += 1;
+                        return false;
+                    }
+                    true
+                });
+                if count < 0 {
+                    list.make_contiguous().reverse();
+                }
+                removed
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Keep only the elements between `start` and `stop` (both inclusive),
+    /// negative indexes count from the end of the list.
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<(), BackendError> {
+        self.write_value(key.to_string(), false, |list: &mut List| {
+            match list_range(list.len(), start, stop) {
+                Some((start, stop)) => {
+                    list.truncate(stop + 1);
+                    list.drain(..start);
+                }
+                None => list.clear(),
+            }
+        })?;
+        Ok(())
+    }
+}
+
+impl Collection for List {
+    fn project(value: &Value) -> Option<&Self> {
+        match value {
+            Value::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    fn project_mut(value: &mut Value) -> Option<&mut Self> {
+        match value {
+            Value::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::List(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        List::is_empty(self)
+    }
+}
+
+fn pop(list: &mut List, end: ListEnd, count: usize) -> Vec<RespFrame> {
+    let count = count.min(list.len());
+    match end {
+        ListEnd::Left => list.drain(..count).collect(),
+        ListEnd::Right => list.drain(list.len() - count..).rev().collect(),
+    }
+}
+
+fn push(list: &mut List, end: ListEnd, value: RespFrame) {
+    match end {
+        ListEnd::Left => list.push_front(value),
+        ListEnd::Right => list.push_back(value),
+    }
+}
+
+// the position of `index` in a list of `len` elements, negative indexes count from the end
+fn list_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 {
+        index.checked_add(len as i64)?
+    } else {
+        index
+    };
+    usize::try_from(index).ok().filter(|&index| index < len)
+}
+
+// the inclusive bounds of `start..=stop` in a list of `len` elements, clamped like redis
+// does: negative indexes count from the end and None means the range is empty
+pub(super) fn list_range(len: usize, start: i64, stop: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+    Some((start as usize, stop as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_range() {
+        assert_eq!(list_range(5, 0, -1), Some((0, 4)));
+        assert_eq!(list_range(5, -3, 2), Some((2, 2)));
+        assert_eq!(list_range(5, -100, 100), Some((0, 4)));
+        assert_eq!(list_range(5, 3, 1), None);
+        assert_eq!(list_range(5, 5, 10), None);
+        assert_eq!(list_range(5, 0, -6), None);
+        assert_eq!(list_range(0, 0, -1), None);
+        assert_eq!(list_index(5, -1), Some(4));
+        assert_eq!(list_index(5, -6), None);
+        assert_eq!(list_index(5, 5), None);
+    }
+}
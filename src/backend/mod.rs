@@ -1,17 +1,135 @@
+mod blocking;
+mod clock;
+mod expire;
+mod glob;
+mod hash;
+mod keyspace;
+mod list;
+mod scan;
+mod set;
+mod skiplist;
+mod stream;
+mod string;
+mod zset;
+
+use std::collections::{HashSet, VecDeque};
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use dashmap::DashMap;
+use dashmap::{
+    mapref::{
+        entry::Entry,
+        one::{Ref, RefMut},
+    },
+    DashMap,
+};
+use thiserror::Error;
 
 use crate::resp::RespFrame;
+use blocking::Blocking;
+use expire::VolatileKeys;
+
+pub use blocking::{Blocked, ServeFn, Waiter};
+#[cfg(test)]
+pub(crate) use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use expire::run_active_expire;
+pub use glob::glob_match;
+pub use hash::Hash;
+pub use list::ListEnd;
+pub use set::SetOp;
+pub use stream::{Stream, StreamFields, StreamId, StreamTrim, TrimStrategy, XAddId, XAddOptions};
+pub use zset::{
+    Aggregate, LexBound, ZAddOptions, ZCombineOptions, ZRangeBy, ZRangeSpec, ZSet, ZSetEnd,
+};
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
 
 #[derive(Debug)]
 pub struct BackendInner {
-    pub map: DashMap<String, RespFrame>,
-    pub hmap: DashMap<String, DashMap<String, RespFrame>>,
+    pub db: DashMap<String, Expiring<Value>>,
+    // single-key operations share this lock while multi-key ones hold it exclusively,
+    // so no client can observe a multi-key operation half applied
+    lock: RwLock<()>,
+    clock: Arc<dyn Clock>,
+    blocking: Blocking,
+    volatile: Mutex<VolatileKeys>,
+}
+
+/// A value in the keyspace, tagged with its type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(RespFrame),
+    Hash(Hash),
+    List(VecDeque<RespFrame>),
+    Set(HashSet<String>),
+    ZSet(ZSet),
+    Stream(Stream),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BackendError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR hash value is not an integer")]
+    HashNotInteger,
+    #[error("ERR hash value is not a float")]
+    HashNotFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR resulting score is not a number (NaN)")]
+    ScoreNaN,
+    #[error("ERR The ID specified in XADD is equal or smaller than the target stream top item")]
+    StreamIdTooSmall,
+    #[error("ERR The stream has exhausted the last possible ID, unable to add more items")]
+    StreamExhausted,
+}
+
+/// A keyspace value together with its optional deadline (unix time in ms).
+#[derive(Debug, Clone, Default)]
+pub struct Expiring<T> {
+    pub value: T,
+    pub expire_at: Option<i64>,
+}
+
+/// NX / XX / GT / LT options of the EXPIRE command family.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExpireCondition {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+}
+
+/// NX / XX options of SET.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    IfNotExists,
+    IfExists,
+}
+
+/// What SET does with the deadline of the key.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SetTtl {
+    #[default]
+    Clear,
+    Keep,
+    At(i64),
 }
 
 impl Deref for Backend {
@@ -27,34 +145,252 @@ impl Default for Backend {
     }
 }
 
+impl Backend {
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self(Arc::new(BackendInner::with_clock(clock)))
+    }
+}
+
+impl Value {
+    /// The type name reported by the TYPE command.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+            Value::Stream(_) => "stream",
+        }
+    }
+}
+
+// a type of value the `read_value` and `write_value` helpers hand out, projected out of
+// the `Value` it is stored as
+trait Collection: Default {
+    // whether an emptied collection stays in the keyspace rather than being deleted
+    const KEEP_EMPTY: bool = false;
+
+    fn project(value: &Value) -> Option<&Self>;
+    fn project_mut(value: &mut Value) -> Option<&mut Self>;
+    fn into_value(self) -> Value;
+    fn is_empty(&self) -> bool;
+}
+
+impl<T> Expiring<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            expire_at: None,
+        }
+    }
+}
+
+impl Expiring<Value> {
+    /// Whether the key is past its deadline, or is a hash whose fields all are.
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expire_at, Some(at) if at <= now)
+            || matches!(&self.value, Value::Hash(hash) if hash.all_fields_expired(now))
+    }
+
+    // whether the key or some of its fields can expire
+    fn has_deadline(&self) -> bool {
+        self.expire_at.is_some() || matches!(&self.value, Value::Hash(hash) if hash.has_deadlines())
+    }
+}
+
+impl ExpireCondition {
+    // a key without a deadline is treated as having an infinite ttl
+    fn allows(&self, current: Option<i64>, at: i64) -> bool {
+        if (self.nx && current.is_some()) || (self.xx && current.is_none()) {
+            return false;
+        }
+        match current {
+            Some(current) => !(self.gt && at <= current || self.lt && at >= current),
+            None => !self.gt,
+        }
+    }
+}
+
 impl BackendInner {
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
-            map: DashMap::new(),
-            hmap: DashMap::new(),
+            db: DashMap::new(),
+            lock: RwLock::new(()),
+            clock,
+            blocking: Blocking::default(),
+            volatile: Mutex::default(),
         }
     }
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+
+    pub fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    // the guards are never held across calls into other locking methods,
+    // and a panic while holding them leaves the keyspace consistent
+    fn shared(&self) -> RwLockReadGuard<'_, ()> {
+        self.lock.read().unwrap_or_else(PoisonError::into_inner)
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+    fn exclusive(&self) -> RwLockWriteGuard<'_, ()> {
+        self.lock.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Look up a key for reading, lazily deleting it if its deadline has passed.
+    fn lookup(&self, key: &str) -> Option<Ref<'_, String, Expiring<Value>>> {
+        let now = self.now_ms();
+        let entry = self.db.get(key)?;
+        if !entry.is_expired(now) {
+            return Some(entry);
+        }
+        drop(entry);
+        self.db.remove_if(key, |_, v| v.is_expired(now));
+        None
+    }
+
+    /// Look up a key for writing, lazily deleting it if its deadline has passed.
+    fn lookup_mut(&self, key: &str) -> Option<RefMut<'_, String, Expiring<Value>>> {
+        let now = self.now_ms();
+        let entry = self.db.get_mut(key)?;
+        if !entry.is_expired(now) {
+            return Some(entry);
+        }
+        drop(entry);
+        self.db.remove_if(key, |_, v| v.is_expired(now));
+        None
+    }
+
+    /// The entry of a key for writing, an expired key is seen as vacant.
+    fn entry(&self, key: String) -> Entry<'_, String, Expiring<Value>> {
+        let now = self.now_ms();
+        self.db.remove_if(&key, |_, v| v.is_expired(now));
+        self.db.entry(key)
+    }
+
+    // run `f` on the collection at `key`, None if the key doesn't exist
+    fn read_value<C: Collection, T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&C) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        let _guard = self.shared();
+        match self.lookup(key) {
+            Some(entry) => match C::project(&entry.value) {
+                Some(collection) => Ok(Some(f(collection))),
+                None => Err(BackendError::WrongType),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // run `f` on the collection at `key` while holding the lock of its shard. A missing
+    // key is created as an empty collection if `create` is set, otherwise `f` is not run
+    // and None is returned. A collection left empty by `f` is deleted, unless its type
+    // keeps empty ones.
+    fn write_value<C: Collection, T>(
+        &self,
+        key: String,
+        create: bool,
+        f: impl FnOnce(&mut C) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        let _guard = self.shared();
+        self.update_value(key, create, f)
+    }
+
+    // `write_value` for callers already holding the keyspace lock
+    fn update_value<C: Collection, T>(
+        &self,
+        key: String,
+        create: bool,
+        f: impl FnOnce(&mut C) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        let mut entry = match self.entry(key) {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) if create => {
+                entry.insert_entry(Expiring::new(C::default().into_value()))
+            }
+            Entry::Vacant(_) => return Ok(None),
+        };
+        let Some(collection) = C::project_mut(&mut entry.get_mut().value) else {
+            return Err(BackendError::WrongType);
+        };
+        let result = f(collection);
+        if collection.is_empty() && !C::KEEP_EMPTY {
+            entry.remove();
+        }
+        Ok(Some(result))
+    }
+
+    // run `f` on the values at `keys`, None for the keys that don't exist. Fails if a
+    // value is not one that `accepts`. The caller must hold the exclusive guard, so
+    // that no other client writes while several entries are held at once.
+    fn read_values<T>(
+        &self,
+        keys: &[String],
+        accepts: impl Fn(&Value) -> bool,
+        f: impl FnOnce(&[Option<&Value>]) -> T,
+    ) -> Result<T, BackendError> {
+        // expired keys are deleted first, lazily expiring them while other entries
+        // of the same shard are held would deadlock
+        for key in keys {
+            if let Some(entry) = self.lookup(key) {
+                if !accepts(&entry.value) {
+                    return Err(BackendError::WrongType);
+                }
+            }
+        }
+        let entries: Vec<_> = keys.iter().map(|key| self.db.get(key)).collect();
+        let values: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.as_deref().map(|entry| &entry.value))
+            .collect();
+        Ok(f(&values))
+    }
+
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        let _guard = self.shared();
+        self.lookup(key).map(|v| v.value.type_name())
+    }
+
+    /// Set the deadline of `key` to `at` (unix time in ms) if `condition` allows it.
+    /// A deadline in the past deletes the key. Returns false if the key doesn't exist
+    /// or the condition is not met.
+    pub fn expire(&self, key: &str, at: i64, condition: ExpireCondition) -> bool {
+        let _guard = self.shared();
+        let Some(mut entry) = self.lookup_mut(key) else {
+            return false;
+        };
+        if !condition.allows(entry.expire_at, at) {
+            return false;
+        }
+        entry.expire_at = Some(at);
+        let now = self.now_ms();
+        let expired = entry.is_expired(now);
+        drop(entry);
+        if expired {
+            self.db.remove_if(key, |_, v| v.is_expired(now));
+        } else {
+            self.track_deadline(key);
+        }
+        true
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_insert_with(DashMap::new);
-        hmap.insert(field, value);
+    /// Returns None if the key doesn't exist, Some(None) if it has no deadline.
+    pub fn expire_at(&self, key: &str) -> Option<Option<i64>> {
+        let _guard = self.shared();
+        self.lookup(key).map(|v| v.expire_at)
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+    /// Remove the deadline of `key`. Returns false if the key doesn't exist or has no deadline.
+    pub fn persist(&self, key: &str) -> bool {
+        let _guard = self.shared();
+        self.lookup_mut(key)
+            .is_some_and(|mut v| v.expire_at.take().is_some())
     }
 }
 
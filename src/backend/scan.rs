@@ -0,0 +1,85 @@
+use hashbrown::raw::RawTable;
+
+// hashbrown starts probing for an entry at bucket `hash & mask` and keeps the top
+// 7 bits of its hash as a tag in the control bytes, see `h1` and `h2` in hashbrown
+const TAG_SHIFT: u32 = if usize::BITS < u64::BITS {
+    usize::BITS - 7
+} else {
+    u64::BITS - 7
+};
+const TAGS: u64 = 1 << 7;
+
+/// Visit the entries of `table` whose home bucket is the one `cursor` points at,
+/// and return the cursor of the next bucket, 0 once every bucket was visited.
+///
+/// Like redis' dictScan, the cursor counts in reverse binary: when the table
+/// doubles, each bucket splits into buckets that are all visited after the ones
+/// already visited. hashbrown tables never shrink unless asked to, so every entry
+/// present for the whole iteration is visited exactly once.
+pub(crate) fn scan_bucket<'a, T>(
+    table: &'a RawTable<T>,
+    cursor: u64,
+    hash: impl Fn(&T) -> u64,
+    mut visit: impl FnMut(&'a T),
+) -> u64 {
+    let mask = table.buckets() as u64 - 1;
+    let home = cursor & mask;
+    // entries sit somewhere on the probe sequence of their home bucket, which is
+    // walked the way a lookup does, once for each tag
+    for tag in 0..TAGS {
+        // SAFETY: the buckets are only read while `table` is borrowed
+        for bucket in unsafe { table.iter_hash(tag << TAG_SHIFT | home) } {
+            let entry = unsafe { bucket.as_ref() };
+            if hash(entry) & mask == home {
+                visit(entry);
+            }
+        }
+    }
+    // increment the reversed cursor, the bits above the mask carry into the next bucket
+    (cursor | !mask)
+        .reverse_bits()
+        .checked_add(1)
+        .map_or(0, u64::reverse_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{hash_map::RandomState, HashSet},
+        hash::BuildHasher,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_scan_bucket_survives_growth() {
+        let mut map: hashbrown::HashMap<String, (), RandomState> = Default::default();
+        for i in 0..100 {
+            map.insert(format!("key{}", i), ());
+        }
+        let hasher = map.hasher().clone();
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        let mut steps = 0;
+        loop {
+            cursor = scan_bucket(
+                map.raw_table(),
+                cursor,
+                |(key, _)| hasher.hash_one(key),
+                |(key, _)| assert!(seen.insert(key.clone()), "{} returned twice", key),
+            );
+            // grow the table several times while iterating
+            steps += 1;
+            if steps % 8 == 0 && steps <= 64 {
+                for i in 0..50 {
+                    map.insert(format!("new{}-{}", steps, i), ());
+                }
+            }
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert!(map.raw_table().buckets() > 128);
+        assert!((0..100).all(|i| seen.contains(&format!("key{}", i))));
+    }
+}
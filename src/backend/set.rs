@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use rand::seq::{IteratorRandom, SliceRandom};
+
+use super::{BackendError, BackendInner, Collection, Expiring, Value};
+
+type Set = HashSet<String>;
+
+/// How SINTER, SUNION and SDIFF combine their sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetOp {
+    Inter,
+    Union,
+    /// The members of the first set that are in none of the others.
+    Diff,
+}
+
+impl BackendInner {
+    /// Add `members` to the set. Returns the number of members that were not already in it.
+    pub fn sadd(&self, key: String, members: Vec<String>) -> Result<usize, BackendError> {
+        Ok(self
+            .write_value(key, true, |set: &mut Set| {
+                members
+                    .into_iter()
+                    .filter(|member| set.insert(member.clone()))
+                    .count()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Remove `members` from the set. Returns the number of members that were in it.
+    pub fn srem(&self, key: &str, members: &[String]) -> Result<usize, BackendError> {
+        Ok(self
+            .write_value(key.to_string(), false, |set: &mut Set| {
+                members.iter().filter(|member| set.remove(*member)).count()
+            })?
+            .unwrap_or_default())
+    }
+
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, BackendError> {
+        Ok(self
+            .read_value(key, |set: &Set| set.iter().cloned().collect())?
+            .unwrap_or_default())
+    }
+
+    pub fn sismember(&self, key: &str, member: &str) -> Result<bool, BackendError> {
+        Ok(self
+            .read_value(key, |set: &Set| set.contains(member))?
+            .unwrap_or_default())
+    }
+
+    pub fn smismember(&self, key: &str, members: &[String]) -> Result<Vec<bool>, BackendError> {
+        Ok(self
+            .read_value(key, |set: &Set| {
+                members.iter().map(|m| set.contains(m)).collect()
+            })?
+            .unwrap_or_else(|| vec![false; members.len()]))
+    }
+
+    pub fn scard(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self
+            .read_value(key, |set: &Set| set.len())?
+            .unwrap_or_default())
+    }
+
+    /// Remove up to `count` random members from the set and return them.
+    pub fn spop(&self, key: &str, count: usize) -> Result<Vec<String>, BackendError> {
+        let mut rng = rand::thread_rng();
+        Ok(self
+            .write_value(key.to_string(), false, |set: &mut Set| {
+                // popping the whole set needs no sampling nor copies
+                if count >= set.len() {
+                    return set.drain().collect();
+                }
+                // only the chosen members are copied
+                let members: Vec<String> = if count == 1 {
+                    set.iter().choose(&mut rng).cloned().into_iter().collect()
+                } else {
+                    let chosen = set.iter().choose_multiple(&mut rng, count);
+                    chosen.into_iter().cloned().collect()
+                };
+                for member in &members {
+                    set.remove(member);
+                }
+                members
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Random members of the set: up to `count` distinct ones if `count` is positive,
+    /// exactly `-count` ones that may repeat if it's negative.
+    pub fn srandmember(&self, key: &str, count: i64) -> Result<Vec<String>, BackendError> {
+        let mut rng = rand::thread_rng();
+        Ok(self
+            .read_value(key, |set: &Set| {
+                if count >= 0 {
+                    let count = usize::try_from(count).unwrap_or(usize::MAX).min(set.len());
+                    let chosen = set.iter().choose_multiple(&mut rng, count);
+                    return chosen.into_iter().cloned().collect();
+                }
+                let members: Vec<_> = set.iter().collect();
+                (0..count.unsigned_abs())
+                    .filter_map(|_| members.choose(&mut rng))
+                    .map(|member| member.to_string())
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Atomically move `member` from the set at `source` to the set at `destination`.
+    /// Returns false if `member` is not in `source`.
+    pub fn smove(
+        &self,
+        source: &str,
+        destination: &str,
+        member: &str,
+    ) -> Result<bool, BackendError> {
+        let _guard = self.exclusive();
+        // like redis, both keys must hold sets even if nothing is moved
+        if let Some(entry) = self.lookup(destination) {
+            if !matches!(entry.value, Value::Set(_)) {
+                return Err(BackendError::WrongType);
+            }
+        }
+        // moving a member onto itself must not delete a set of one member on the way
+        if source == destination {
+            return Ok(self
+                .update_value(source.to_string(), false, |set: &mut Set| {
+                    set.contains(member)
+                })?
+                .unwrap_or_default());
+        }
+        let Some(moved) = self.update_value(source.to_string(), false, |set: &mut Set| {
+            set.remove(member)
+        })?
+        else {
+            return Ok(false);
+        };
+        if moved {
+            self.update_value(destination.to_string(), true, |set: &mut Set| {
+                set.insert(member.to_string())
+            })?;
+        }
+        Ok(moved)
+    }
+
+    /// Combine the sets at `keys` with `op`, a missing key counts as an empty set.
+    pub fn sets_combine(&self, op: SetOp, keys: &[String]) -> Result<Vec<String>, BackendError> {
+        let _guard = self.exclusive();
+        let set = self.with_sets(keys, |sets| combine(op, sets))?;
+        Ok(set.into_iter().collect())
+    }
+
+    /// Like `sets_combine`, but the result replaces whatever `destination` held.
+    /// Returns the size of the result, an empty one deletes `destination`.
+    pub fn sets_combine_store(
+        &self,
+        op: SetOp,
+        destination: String,
+        keys: &[String],
+    ) -> Result<usize, BackendError> {
+        let _guard = self.exclusive();
+        let set = self.with_sets(keys, |sets| combine(op, sets))?;
+        let len = set.len();
+        self.db.remove(&destination);
+        if len > 0 {
+            self.db.insert(destination, Expiring::new(Value::Set(set)));
+        }
+        Ok(len)
+    }
+
+    /// The size of the intersection of the sets at `keys`, counting stops once it
+    /// reaches `limit` (0 counts the whole intersection).
+    pub fn sintercard(&self, keys: &[String], limit: usize) -> Result<usize, BackendError> {
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        let _guard = self.exclusive();
+        self.with_sets(keys, |sets| {
+            let Some((smallest, others)) = by_size(sets) else {
+                return 0;
+            };
+            smallest
+                .iter()
+                .filter(|member| others.iter().all(|set| set.contains(*member)))
+                .take(limit)
+                .count()
+        })
+    }
+
+    // run `f` on the sets at `keys`, None for the keys that don't exist. The caller
+    // must hold the exclusive guard, see `read_values`.
+    fn with_sets<T>(
+        &self,
+        keys: &[String],
+        f: impl FnOnce(&[Option<&Set>]) -> T,
+    ) -> Result<T, BackendError> {
+        self.read_values(
+            keys,
+            |value| Set::project(value).is_some(),
+            |values| {
+                let sets: Vec<_> = values.iter().map(|v| v.and_then(Set::project)).collect();
+                f(&sets)
+            },
+        )
+    }
+}
+
+impl Collection for Set {
+    fn project(value: &Value) -> Option<&Self> {
+        match value {
+            Value::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    fn project_mut(value: &mut Value) -> Option<&mut Self> {
+        match value {
+            Value::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Set(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Set::is_empty(self)
+    }
+}
+
+fn combine(op: SetOp, sets: &[Option<&Set>]) -> Set {
+    match op {
+        SetOp::Inter => match by_size(sets) {
+            Some((smallest, others)) => smallest
+                .iter()
+                .filter(|member| others.iter().all(|set| set.contains(*member)))
+                .cloned()
+                .collect(),
+            None => Set::new(),
+        },
+        SetOp::Union => sets
+            .iter()
+            .flatten()
+            .flat_map(|set| set.iter())
+            .cloned()
+            .collect(),
+        SetOp::Diff => {
+            let Some(Some(first)) = sets.first() else {
+                return Set::new();
+            };
+            first
+                .iter()
+                .filter(|member| !sets[1..].iter().flatten().any(|set| set.contains(*member)))
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+// the smallest of `sets` and the others, to intersect them by walking the smallest.
+// None if one of them is missing, which makes the intersection empty.
+fn by_size<'a>(sets: &[Option<&'a Set>]) -> Option<(&'a Set, Vec<&'a Set>)> {
+    let mut sets: Vec<&Set> = sets.iter().copied().collect::<Option<_>>()?;
+    sets.sort_by_key(|set| set.len());
+    let smallest = sets.first().copied()?;
+    Some((smallest, sets.split_off(1)))
+}
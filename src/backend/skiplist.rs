@@ -0,0 +1,360 @@
+use std::fmt;
+
+// like redis, a node reaches each next level with a probability of 1/4
+const MAX_LEVEL: usize = 32;
+const P: f64 = 0.25;
+
+// the head node, which holds no item
+const HEAD: usize = 0;
+
+/// Items in ascending order, with the number of items each link skips over so that
+/// the rank of an item is found in O(log N), as in the skip list of a redis sorted set.
+/// The nodes live in a vector and link to each other by index.
+#[derive(Clone)]
+pub struct SkipList<T> {
+    nodes: Vec<Node<T>>,
+    // slots of removed nodes, reused by the next inserts
+    free: Vec<usize>,
+    len: usize,
+    // the number of levels in use
+    level: usize,
+    tail: Option<usize>,
+}
+
+#[derive(Clone)]
+struct Node<T> {
+    item: Option<T>,
+    links: Vec<Link>,
+    prev: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Link {
+    next: Option<usize>,
+    // the number of items between this node and `next`, counting `next`. A link
+    // without `next` counts the items up to the end of the list.
+    span: usize,
+}
+
+/// Iterator over a run of consecutive items of a `SkipList`.
+pub struct Iter<'a, T> {
+    list: &'a SkipList<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+impl<T: Ord> SkipList<T> {
+    pub fn new() -> Self {
+        let head = Node {
+            item: None,
+            links: vec![Link::default(); MAX_LEVEL],
+            prev: None,
+        };
+        Self {
+            nodes: vec![head],
+            free: vec![],
+            len: 0,
+            level: 1,
+            tail: None,
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.nodes[HEAD].links[0].next.map(|node| self.item(node))
+    }
+
+    /// The number of leading items `before` holds for. `before` must hold for a
+    /// prefix of the list, like the predicate of `slice::partition_point`.
+    pub fn count_while(&self, before: impl Fn(&T) -> bool) -> usize {
+        self.path(before).1[0]
+    }
+
+    pub fn insert(&mut self, item: T) {
+        let (mut update, mut rank) = self.path(|other| other < &item);
+        let level = random_level();
+        if level > self.level {
+            for i in self.level..level {
+                update[i] = HEAD;
+                rank[i] = 0;
+                self.nodes[HEAD].links[i].span = self.len;
+            }
+            self.level = level;
+        }
+        let node = self.alloc(item, level);
+        for i in 0..level {
+            let skipped = rank[0] - rank[i];
+            let prev = self.nodes[update[i]].links[i];
+            self.nodes[node].links[i] = Link {
+                next: prev.next,
+                span: prev.span - skipped,
+            };
+            self.nodes[update[i]].links[i] = Link {
+                next: Some(node),
+                span: skipped + 1,
+            };
+        }
+        for (i, &prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[prev].links[i].span += 1;
+        }
+        self.nodes[node].prev = (update[0] != HEAD).then_some(update[0]);
+        match self.nodes[node].links[0].next {
+            Some(next) => self.nodes[next].prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// Remove the item equal to `item`, returning it.
+    pub fn remove(&mut self, item: &T) -> Option<T> {
+        let (update, _) = self.path(|other| other < item);
+        let node = self.nodes[update[0]].links[0]
+            .next
+            .filter(|&node| self.item(node) == item)?;
+        Some(self.unlink(node, &update))
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        let node = self.nodes[HEAD].links[0].next?;
+        Some(self.unlink(node, &[HEAD; MAX_LEVEL]))
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        let node = self.tail?;
+        let item = self.item(node);
+        let (update, _) = self.path(|other| other < item);
+        Some(self.unlink(node, &update))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.nodes[HEAD].links[0].next,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    /// The items with ranks `start..end`, found in O(log N).
+    pub fn ranks(&self, start: usize, end: usize) -> Iter<'_, T> {
+        let len = end.min(self.len).saturating_sub(start);
+        Iter {
+            list: self,
+            front: (len > 0).then(|| self.node_at(start)).flatten(),
+            back: (len > 0).then(|| self.node_at(start + len - 1)).flatten(),
+            len,
+        }
+    }
+
+    fn item(&self, node: usize) -> &T {
+        self.nodes[node]
+            .item
+            .as_ref()
+            .expect("only the head holds no item")
+    }
+
+    // the last node `before` holds for on each level, and the number of items up to
+    // and including it
+    fn path(&self, before: impl Fn(&T) -> bool) -> ([usize; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut node = HEAD;
+        let mut traversed = 0;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[node].links[i].next {
+                if !before(self.item(next)) {
+                    break;
+                }
+                traversed += self.nodes[node].links[i].span;
+                node = next;
+            }
+            update[i] = node;
+            rank[i] = traversed;
+        }
+        (update, rank)
+    }
+
+    // the node with the 0-based `rank`
+    fn node_at(&self, rank: usize) -> Option<usize> {
+        // count the head as rank 0
+        let target = rank + 1;
+        let mut node = HEAD;
+        let mut traversed = 0;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[node].links[i].next {
+                let span = self.nodes[node].links[i].span;
+                if traversed + span > target {
+                    break;
+                }
+                traversed += span;
+                node = next;
+            }
+            if traversed == target {
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    fn alloc(&mut self, item: T, level: usize) -> usize {
+        let node = Node {
+            item: Some(item),
+            links: vec![Link::default(); level],
+            prev: None,
+        };
+        match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // take `node` out of the list, `update` holds the node before it on each level
+    fn unlink(&mut self, node: usize, update: &[usize; MAX_LEVEL]) -> T {
+        for (i, &prev) in update.iter().enumerate().take(self.level) {
+            let link = self.nodes[node].links.get(i).copied();
+            let prev = &mut self.nodes[prev].links[i];
+            match link {
+                Some(link) if prev.next == Some(node) => {
+                    prev.span = prev.span + link.span - 1;
+                    prev.next = link.next;
+                }
+                _ => prev.span -= 1,
+            }
+        }
+        let prev = self.nodes[node].prev;
+        match self.nodes[node].links[0].next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+        while self.level > 1 && self.nodes[HEAD].links[self.level - 1].next.is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        self.free.push(node);
+        let node = &mut self.nodes[node];
+        node.links = vec![];
+        node.prev = None;
+        node.item.take().expect("only the head holds no item")
+    }
+}
+
+fn random_level() -> usize {
+    let mut level = 1;
+    while level < MAX_LEVEL && rand::random::<f64>() < P {
+        level += 1;
+    }
+    level
+}
+
+impl<T: Ord> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> PartialEq for SkipList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for SkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front?;
+        self.front = self.list.nodes[node].links[0].next;
+        self.len -= 1;
+        Some(self.list.item(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Ord> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back?;
+        self.back = self.list.nodes[node].prev;
+        self.len -= 1;
+        Some(self.list.item(node))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_skip_list_matches_btree_set() {
+        let mut rng = rand::thread_rng();
+        let mut list = SkipList::new();
+        let mut model = BTreeSet::new();
+        for _ in 0..5000 {
+            let item = rng.gen_range(0..500);
+            if rng.gen_bool(0.6) {
+                if model.insert(item) {
+                    list.insert(item);
+                }
+            } else {
+                assert_eq!(list.remove(&item), model.take(&item));
+            }
+        }
+        assert_eq!(list.iter().len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+        for item in 0..500 {
+            assert_eq!(
+                list.count_while(|other| other < &item),
+                model.range(..item).count()
+            );
+        }
+        let items: Vec<_> = model.iter().collect();
+        for (start, end) in [(0, 1), (10, 50), (40, items.len()), (0, items.len() + 5)] {
+            let end_in_list = end.min(items.len());
+            assert!(list
+                .ranks(start, end)
+                .eq(items[start..end_in_list].iter().copied()));
+            assert!(list
+                .ranks(start, end)
+                .rev()
+                .eq(items[start..end_in_list].iter().rev().copied()));
+        }
+        assert_eq!(list.ranks(50, 10).len(), 0);
+        assert_eq!(list.ranks(items.len(), items.len() + 1).next(), None);
+
+        while let Some(last) = model.pop_last() {
+            assert_eq!(list.pop_last(), Some(last));
+            let Some(first) = model.pop_first() else {
+                break;
+            };
+            assert_eq!(list.pop_first(), Some(first));
+        }
+        assert_eq!(list.iter().next(), None);
+    }
+}
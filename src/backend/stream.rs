@@ -0,0 +1,395 @@
+use std::{collections::BTreeMap, fmt};
+
+use dashmap::mapref::entry::Entry;
+
+use crate::resp::RespFrame;
+
+use super::{BackendError, BackendInner, Collection, Expiring, Value};
+
+// redis stores stream entries in nodes of this many entries and only trims whole
+// nodes when trimming approximately, the trimming emulates that
+const STREAM_NODE_MAX_ENTRIES: usize = 100;
+
+/// The field-value pairs of a stream entry, in the order they were added.
+pub type StreamFields = Vec<(String, RespFrame)>;
+
+/// An append-only log of entries ordered by ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    // the largest ID ever added, which new IDs must be greater than even after
+    // that entry was deleted
+    last_id: StreamId,
+}
+
+/// The ID of a stream entry, `<ms>-<seq>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+/// The ID XADD gives a new entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XAddId {
+    /// `*`: the current time, or right after the last ID if the clock is behind it.
+    Auto,
+    /// `<ms>-*`: the next sequence number of `ms`.
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+/// NOMKSTREAM / MAXLEN / MINID options of XADD.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct XAddOptions {
+    /// Don't create the stream if it doesn't exist.
+    pub nomkstream: bool,
+    pub trim: Option<StreamTrim>,
+}
+
+/// MAXLEN / MINID options of XADD and XTRIM.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamTrim {
+    pub strategy: TrimStrategy,
+    /// `~` trims whole nodes only, which may leave more entries than asked for.
+    pub approximate: bool,
+    /// The most entries an approximate trim removes, 0 for no limit. None is
+    /// 100 nodes like in redis.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimStrategy {
+    /// Keep at most this many entries.
+    MaxLen(usize),
+    /// Remove the entries with a smaller ID.
+    MinId(StreamId),
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    /// The ID right after this one, None for the largest ID.
+    pub fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_add(1)?, 0)),
+        }
+    }
+
+    /// The ID right before this one, None for the smallest ID.
+    pub fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_sub(1)?, u64::MAX)),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl Stream {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Append an entry, returning its ID. `now_ms` is the time `*` IDs are made of.
+    pub fn add(
+        &mut self,
+        id: XAddId,
+        now_ms: u64,
+        fields: StreamFields,
+    ) -> Result<StreamId, BackendError> {
+        let last = self.last_id;
+        let id = match id {
+            XAddId::Auto if now_ms > last.ms => StreamId::new(now_ms, 0),
+            XAddId::Auto => last.next().ok_or(BackendError::StreamExhausted)?,
+            XAddId::AutoSeq(ms) if ms > last.ms => StreamId::new(ms, 0),
+            XAddId::AutoSeq(ms) if ms == last.ms => StreamId::new(
+                ms,
+                last.seq
+                    .checked_add(1)
+                    .ok_or(BackendError::StreamIdTooSmall)?,
+            ),
+            XAddId::AutoSeq(_) => return Err(BackendError::StreamIdTooSmall),
+            XAddId::Explicit(id) => id,
+        };
+        if id <= last {
+            return Err(BackendError::StreamIdTooSmall);
+        }
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(id)
+    }
+
+    /// Remove the oldest entries as `trim` asks. Returns the number of entries removed.
+    pub fn trim(&mut self, trim: &StreamTrim) -> usize {
+        let excess = match trim.strategy {
+            TrimStrategy::MaxLen(max_len) => self.len().saturating_sub(max_len),
+            TrimStrategy::MinId(min_id) => self.entries.range(..min_id).count(),
+        };
+        let count = if trim.approximate {
+            let count = match trim.limit.unwrap_or(100 * STREAM_NODE_MAX_ENTRIES) {
+                0 => excess,
+                limit => excess.min(limit),
+            };
+            count - count % STREAM_NODE_MAX_ENTRIES
+        } else {
+            excess
+        };
+        for _ in 0..count {
+            self.entries.pop_first();
+        }
+        count
+    }
+
+    /// Remove the entries with `ids`. Returns the number of entries removed.
+    pub fn delete(&mut self, ids: &[StreamId]) -> usize {
+        ids.iter()
+            .filter(|id| self.entries.remove(id).is_some())
+            .count()
+    }
+
+    /// The entries from `start` to `end` (both inclusive), at most `count` of them,
+    /// from the newest one if `rev` is set.
+    pub fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Vec<(StreamId, &StreamFields)> {
+        // BTreeMap::range panics on these, they select nothing anyway
+        if start > end {
+            return vec![];
+        }
+        let entries = self.entries.range(start..=end);
+        let entries: Box<dyn Iterator<Item = (&StreamId, &StreamFields)>> = if rev {
+            Box::new(entries.rev())
+        } else {
+            Box::new(entries)
+        };
+        entries
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| (*id, fields))
+            .collect()
+    }
+}
+
+impl BackendInner {
+    /// Append an entry to the stream, creating it unless `options` say otherwise, then
+    /// trim the stream. Returns the ID of the entry, None if the stream doesn't exist
+    /// and NOMKSTREAM was given.
+    pub fn xadd(
+        &self,
+        key: String,
+        id: XAddId,
+        fields: StreamFields,
+        options: &XAddOptions,
+    ) -> Result<Option<StreamId>, BackendError> {
+        let now_ms = u64::try_from(self.now_ms()).unwrap_or_default();
+        let add = |stream: &mut Stream| {
+            let id = stream.add(id, now_ms, fields)?;
+            if let Some(trim) = &options.trim {
+                stream.trim(trim);
+            }
+            Ok(Some(id))
+        };
+        let _guard = self.shared();
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => match &mut entry.get_mut().value {
+                Value::Stream(stream) => add(stream),
+                _ => Err(BackendError::WrongType),
+            },
+            Entry::Vacant(_) if options.nomkstream => Ok(None),
+            // the stream is only created once the entry could be added to it
+            Entry::Vacant(entry) => {
+                let mut stream = Stream::default();
+                let id = add(&mut stream)?;
+                entry.insert(Expiring::new(Value::Stream(stream)));
+                Ok(id)
+            }
+        }
+    }
+
+    pub fn xlen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.read_value(key, Stream::len)?.unwrap_or_default())
+    }
+
+    /// The entries from `start` to `end` (both inclusive), see `Stream::range`.
+    pub fn xrange(
+        &self,
+        key: &str,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Result<Vec<(StreamId, StreamFields)>, BackendError> {
+        Ok(self
+            .read_value(key, |stream: &Stream| {
+                stream
+                    .range(start, end, rev, count)
+                    .into_iter()
+                    .map(|(id, fields)| (id, fields.clone()))
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Trim the stream, returning the number of entries removed.
+    pub fn xtrim(&self, key: &str, trim: &StreamTrim) -> Result<usize, BackendError> {
+        Ok(self
+            .write_value(key.to_string(), false, |stream: &mut Stream| {
+                stream.trim(trim)
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Remove the entries with `ids`, returning the number of entries removed.
+    pub fn xdel(&self, key: &str, ids: &[StreamId]) -> Result<usize, BackendError> {
+        Ok(self
+            .write_value(key.to_string(), false, |stream: &mut Stream| {
+                stream.delete(ids)
+            })?
+            .unwrap_or_default())
+    }
+}
+
+impl Collection for Stream {
+    // redis keeps streams around once their entries are gone
+    const KEEP_EMPTY: bool = true;
+
+    fn project(value: &Value) -> Option<&Self> {
+        match value {
+            Value::Stream(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    fn project_mut(value: &mut Value) -> Option<&mut Self> {
+        match value {
+            Value::Stream(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Stream(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Stream::is_empty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(stream: &mut Stream, id: XAddId, now_ms: u64) -> Result<StreamId, BackendError> {
+        stream.add(id, now_ms, vec![])
+    }
+
+    #[test]
+    fn test_stream_ids() {
+        let mut stream = Stream::default();
+        assert_eq!(add(&mut stream, XAddId::Auto, 5), Ok(StreamId::new(5, 0)));
+        assert_eq!(add(&mut stream, XAddId::Auto, 5), Ok(StreamId::new(5, 1)));
+        // a clock going backwards doesn't make IDs go backwards
+        assert_eq!(add(&mut stream, XAddId::Auto, 3), Ok(StreamId::new(5, 2)));
+        assert_eq!(
+            add(&mut stream, XAddId::AutoSeq(5), 0),
+            Ok(StreamId::new(5, 3))
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::AutoSeq(7), 0),
+            Ok(StreamId::new(7, 0))
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::AutoSeq(6), 0),
+            Err(BackendError::StreamIdTooSmall)
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::Explicit(StreamId::new(7, 0)), 0),
+            Err(BackendError::StreamIdTooSmall)
+        );
+
+        // deleting the last entry doesn't let its ID be reused
+        assert_eq!(
+            stream.delete(&[StreamId::new(7, 0), StreamId::new(9, 9)]),
+            1
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::Explicit(StreamId::new(7, 0)), 0),
+            Err(BackendError::StreamIdTooSmall)
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::Explicit(StreamId::MAX), 0),
+            Ok(StreamId::MAX)
+        );
+        assert_eq!(
+            add(&mut stream, XAddId::Auto, 0),
+            Err(BackendError::StreamExhausted)
+        );
+        assert_eq!(stream.len(), 5);
+
+        assert_eq!(StreamId::new(1, u64::MAX).next(), Some(StreamId::new(2, 0)));
+        assert_eq!(StreamId::new(2, 0).prev(), Some(StreamId::new(1, u64::MAX)));
+        assert_eq!(StreamId::MIN.prev(), None);
+        assert_eq!(StreamId::new(1, 2).to_string(), "1-2");
+    }
+
+    #[test]
+    fn test_stream_trim_and_range() {
+        let mut stream = Stream::default();
+        for ms in 1..=250 {
+            add(&mut stream, XAddId::AutoSeq(ms), 0).unwrap();
+        }
+        let ids = |entries: Vec<(StreamId, &StreamFields)>| -> Vec<u64> {
+            entries.into_iter().map(|(id, _)| id.ms).collect()
+        };
+        let start = StreamId::new(10, 0);
+        let end = StreamId::new(20, 0);
+        assert_eq!(ids(stream.range(start, end, false, Some(3))), [10, 11, 12]);
+        assert_eq!(ids(stream.range(start, end, true, Some(2))), [20, 19]);
+        assert!(stream.range(end, start, false, None).is_empty());
+
+        // approximate trimming only removes whole nodes
+        let mut trim = StreamTrim {
+            strategy: TrimStrategy::MaxLen(60),
+            approximate: true,
+            limit: None,
+        };
+        assert_eq!(stream.trim(&trim), 100);
+        assert_eq!(stream.len(), 150);
+        trim.limit = Some(50);
+        assert_eq!(stream.trim(&trim), 0);
+        trim.approximate = false;
+        trim.limit = None;
+        assert_eq!(stream.trim(&trim), 90);
+        assert_eq!(stream.len(), 60);
+
+        trim.strategy = TrimStrategy::MinId(StreamId::new(200, 0));
+        assert_eq!(stream.trim(&trim), 9);
+        assert_eq!(
+            ids(stream.range(StreamId::MIN, StreamId::MAX, false, Some(1))),
+            [200]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::borrow::Cow;
+
+use dashmap::mapref::entry::Entry;
+
+use crate::resp::{encode::format_double, BulkString, RespFrame};
+
+use super::{BackendError, BackendInner, Expiring, SetCondition, SetTtl, Value};
+
+// same limit as redis' proto-max-bulk-len
+const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+impl BackendInner {
+    pub fn get(&self, key: &str) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.shared();
+        match self.lookup(key).as_deref() {
+            Some(Expiring {
+                value: Value::String(value),
+                ..
+            }) => Ok(Some(value.clone())),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set(&self, key: String, value: RespFrame) {
+        let _guard = self.shared();
+        self.db.insert(key, Expiring::new(Value::String(value)));
+    }
+
+    /// Atomically set `key` if `condition` holds. Returns whether the value was written,
+    /// and the previous value of the key. With `get`, the previous value must be a string.
+    pub fn set_with(
+        &self,
+        key: String,
+        value: RespFrame,
+        condition: SetCondition,
+        ttl: SetTtl,
+        get: bool,
+    ) -> Result<(bool, Option<RespFrame>), BackendError> {
+        let _guard = self.shared();
+        let value = Value::String(value);
+        let tracked = matches!(ttl, SetTtl::At(_)).then(|| key.clone());
+        let old = match self.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let old = match &entry.get().value {
+                    Value::String(old) => Some(old.clone()),
+                    _ if get => return Err(BackendError::WrongType),
+                    _ => None,
+                };
+                if condition == SetCondition::IfNotExists {
+                    return Ok((false, old));
+                }
+                let expire_at = match ttl {
+                    SetTtl::Clear => None,
+                    SetTtl::Keep => entry.get().expire_at,
+                    SetTtl::At(at) => Some(at),
+                };
+                entry.insert(Expiring { value, expire_at });
+                old
+            }
+            Entry::Vacant(entry) => {
+                if condition == SetCondition::IfExists {
+                    return Ok((false, None));
+                }
+                let expire_at = match ttl {
+                    SetTtl::At(at) => Some(at),
+                    _ => None,
+                };
+                entry.insert(Expiring { value, expire_at });
+                None
+            }
+        };
+        if let Some(key) = tracked {
+            self.track_deadline(&key);
+        }
+        Ok((true, old))
+    }
+
+    /// Get the string at `key` and delete it.
+    pub fn getdel(&self, key: &str) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.shared();
+        match self.entry(key.to_string()) {
+            Entry::Occupied(entry) => {
+                let Value::String(value) = &entry.get().value else {
+                    return Err(BackendError::WrongType);
+                };
+                let value = value.clone();
+                entry.remove();
+                Ok(Some(value))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    /// Get the string at `key` and update its deadline, a deadline in the past deletes the key.
+    pub fn getex(&self, key: &str, ttl: SetTtl) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.shared();
+        let Some(mut entry) = self.lookup_mut(key) else {
+            return Ok(None);
+        };
+        let Value::String(value) = &entry.value else {
+            return Err(BackendError::WrongType);
+        };
+        let value = value.clone();
+        match ttl {
+            SetTtl::Keep => {}
+            SetTtl::Clear => entry.expire_at = None,
+            SetTtl::At(at) => entry.expire_at = Some(at),
+        }
+        let now = self.now_ms();
+        let expired = entry.is_expired(now);
+        drop(entry);
+        if expired {
+            self.db.remove_if(key, |_, v| v.is_expired(now));
+        } else if let SetTtl::At(_) = ttl {
+            self.track_deadline(key);
+        }
+        Ok(Some(value))
+    }
+
+    /// The values of `keys` read atomically, keys that don't hold a string are None.
+    pub fn mget(&self, keys: &[String]) -> Vec<Option<RespFrame>> {
+        let _guard = self.shared();
+        keys.iter()
+            .map(|key| match self.lookup(key).as_deref() {
+                Some(Expiring {
+                    value: Value::String(value),
+                    ..
+                }) => Some(value.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Set all the pairs at once, no other client can see only some of them written.
+    pub fn mset(&self, pairs: Vec<(String, RespFrame)>) {
+        let _guard = self.exclusive();
+        for (key, value) in pairs {
+            self.db.insert(key, Expiring::new(Value::String(value)));
+        }
+    }
+
+    /// Like `mset`, but only if none of the keys exist. Returns whether the pairs were set.
+    pub fn msetnx(&self, pairs: Vec<(String, RespFrame)>) -> bool {
+        let _guard = self.exclusive();
+        if pairs.iter().any(|(key, _)| self.lookup(key).is_some()) {
+            return false;
+        }
+        for (key, value) in pairs {
+            self.db.insert(key, Expiring::new(Value::String(value)));
+        }
+        true
+    }
+
+    /// Atomically add `delta` to the integer stored at `key`, a missing key counts as 0.
+    pub fn incr_by(&self, key: String, delta: i64) -> Result<i64, BackendError> {
+        self.update_string(key, |current| {
+            let current = match current {
+                Some(current) => parse_integer(current).ok_or(BackendError::NotInteger)?,
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+            Ok((Some(value.to_string().into_bytes()), value))
+        })
+    }
+
+    /// Atomically add `delta` to the float stored at `key`, a missing key counts as 0.
+    pub fn incr_by_float(&self, key: String, delta: f64) -> Result<f64, BackendError> {
+        self.update_string(key, |current| {
+            let current = match current {
+                Some(current) => parse_float(current).ok_or(BackendError::NotFloat)?,
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(BackendError::NanOrInfinity);
+            }
+            Ok((Some(format_double(value).into_bytes()), value))
+        })
+    }
+
+    /// Append `value` to the string at `key`. Returns the new length.
+    pub fn append(&self, key: String, value: &[u8]) -> Result<usize, BackendError> {
+        Ok(self
+            .edit_string(key, true, |current| {
+                if current.len() + value.len() > MAX_STRING_LEN {
+                    return Err(BackendError::StringTooLong);
+                }
+                current.extend_from_slice(value);
+                Ok(current.len())
+            })?
+            .unwrap_or_default())
+    }
+
+    pub fn strlen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.get(key)?.map_or(0, |value| string_bytes(&value).len()))
+    }
+
+    /// The substring between `start` and `end` (both inclusive),
+    /// negative offsets count from the end of the string.
+    pub fn getrange(&self, key: &str, start: i64, end: i64) -> Result<Vec<u8>, BackendError> {
+        let Some(value) = self.get(key)? else {
+            return Ok(vec![]);
+        };
+        let value = string_bytes(&value);
+        let len = value.len() as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 {
+            (len + end).max(0)
+        } else {
+            end.min(len - 1)
+        };
+        if len == 0 || start > end {
+            return Ok(vec![]);
+        }
+        Ok(value[start as usize..=end as usize].to_vec())
+    }
+
+    /// Overwrite the string at `key` from `offset` on, padding it with zero bytes
+    /// if it is too short. Returns the new length.
+    pub fn setrange(
+        &self,
+        key: String,
+        offset: usize,
+        value: &[u8],
+    ) -> Result<usize, BackendError> {
+        // an empty value doesn't create or change anything
+        Ok(self
+            .edit_string(key, !value.is_empty(), |current| {
+                if value.is_empty() {
+                    return Ok(current.len());
+                }
+                if offset + value.len() > MAX_STRING_LEN {
+                    return Err(BackendError::StringTooLong);
+                }
+                if current.len() < offset + value.len() {
+                    current.resize(offset + value.len(), 0);
+                }
+                current[offset..offset + value.len()].copy_from_slice(value);
+                Ok(current.len())
+            })?
+            .unwrap_or_default())
+    }
+
+    // modify the bytes of the string at `key` in place while holding the lock of its
+    // shard. A missing key is created from the bytes `f` leaves in an empty string if
+    // `create` is set, otherwise `f` is not run and None is returned. `f` must check
+    // its arguments before changing anything, an error leaves the key as it was.
+    fn edit_string<T>(
+        &self,
+        key: String,
+        create: bool,
+        f: impl FnOnce(&mut Vec<u8>) -> Result<T, BackendError>,
+    ) -> Result<Option<T>, BackendError> {
+        let _guard = self.shared();
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let Value::String(current) = &mut entry.get_mut().value else {
+                    return Err(BackendError::WrongType);
+                };
+                if let RespFrame::BulkString(bytes) = current {
+                    return f(&mut bytes.0).map(Some);
+                }
+                let mut bytes = string_bytes(current).into_owned();
+                let result = f(&mut bytes)?;
+                *current = BulkString(bytes).into();
+                Ok(Some(result))
+            }
+            Entry::Vacant(entry) if create => {
+                let mut bytes = vec![];
+                let result = f(&mut bytes)?;
+                entry.insert(Expiring::new(Value::String(BulkString(bytes).into())));
+                Ok(Some(result))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    // read-modify-write the string at `key` while holding the lock of its shard.
+    // `f` gets the current bytes (None if the key doesn't exist) and returns the
+    // bytes to store, None to leave the key untouched. The deadline is kept.
+    fn update_string<T>(
+        &self,
+        key: String,
+        f: impl FnOnce(Option<&[u8]>) -> Result<(Option<Vec<u8>>, T), BackendError>,
+    ) -> Result<T, BackendError> {
+        let _guard = self.shared();
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let Value::String(current) = &entry.get().value else {
+                    return Err(BackendError::WrongType);
+                };
+                let (value, result) = f(Some(&string_bytes(current)))?;
+                if let Some(value) = value {
+                    entry.get_mut().value = Value::String(BulkString(value).into());
+                }
+                Ok(result)
+            }
+            Entry::Vacant(entry) => {
+                let (value, result) = f(None)?;
+                if let Some(value) = value {
+                    entry.insert(Expiring::new(Value::String(BulkString(value).into())));
+                }
+                Ok(result)
+            }
+        }
+    }
+}
+
+// the bytes of a string value, as GET would return them
+pub(super) fn string_bytes(frame: &RespFrame) -> Cow<'_, [u8]> {
+    match frame {
+        RespFrame::BulkString(s) => Cow::Borrowed(s.as_slice()),
+        RespFrame::SimpleSting(s) => Cow::Borrowed(s.as_bytes()),
+        RespFrame::Integer(i) => Cow::Owned(i.to_string().into_bytes()),
+        RespFrame::Double(d) => Cow::Owned(format_double(*d).into_bytes()),
+        // commands only ever store bulk strings
+        _ => Cow::Borrowed(&[]),
+    }
+}
+
+// same rules as redis' string2ll: no '+', no leading zeros and no spaces
+pub(super) fn parse_integer(bytes: &[u8]) -> Option<i64> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty()
+        || (digits.starts_with('0') && s.len() > 1)
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+pub(super) fn parse_float(bytes: &[u8]) -> Option<f64> {
+    std::str::from_utf8(bytes)
+        .ok()?
+        .parse::<f64>()
+        .ok()
+        .filter(|value| !value.is_nan())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer(b"0"), Some(0));
+        assert_eq!(parse_integer(b"-42"), Some(-42));
+        assert_eq!(parse_integer(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_integer(b"9223372036854775808"), None);
+        assert_eq!(parse_integer(b"+1"), None);
+        assert_eq!(parse_integer(b"01"), None);
+        assert_eq!(parse_integer(b"-0"), None);
+        assert_eq!(parse_integer(b" 1"), None);
+        assert_eq!(parse_integer(b""), None);
+        assert_eq!(parse_integer(b"1.5"), None);
+    }
+}
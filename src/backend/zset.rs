@@ -0,0 +1,799 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Bound,
+};
+
+use super::{
+    list::list_range, skiplist::SkipList, BackendError, BackendInner, Collection, Expiring,
+    SetCondition, SetOp, Value,
+};
+
+/// The members of a sorted set, ordered by score and then by member.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZSet {
+    scores: HashMap<String, f64>,
+    ordered: SkipList<ScoredMember>,
+}
+
+// a score that is never NaN, so that it can be totally ordered
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Score(f64);
+
+/// NX / XX / GT / LT / CH options of ZADD.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZAddOptions {
+    /// NX only adds new members, XX only updates existing ones.
+    pub condition: SetCondition,
+    /// Only update a member if its new score is greater than its current one.
+    pub gt: bool,
+    /// Only update a member if its new score is less than its current one.
+    pub lt: bool,
+    /// Count the updated members along with the added ones.
+    pub ch: bool,
+}
+
+/// The end of a sorted set members are popped from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZSetEnd {
+    Min,
+    Max,
+}
+
+/// WEIGHTS / AGGREGATE options of ZUNION, ZINTER and their STORE variants.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZCombineOptions {
+    /// The factor the scores of each input are multiplied by, 1 if there are none.
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+}
+
+/// How the scores of a member found in several inputs are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+/// The members a ZRANGE query selects, in ascending order unless `rev` is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZRangeSpec {
+    pub by: ZRangeBy,
+    pub rev: bool,
+    /// Skip `offset` selected members and return at most `count` of the rest.
+    pub limit: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    /// Ranks between `start` and `stop` (both inclusive), negative ranks count from the end.
+    Rank(i64, i64),
+    /// Scores between a min and a max.
+    Score(Bound<f64>, Bound<f64>),
+    /// Members between a min and a max. Like in redis, this is only meaningful when
+    /// all the members have the same score.
+    Lex(LexBound, LexBound),
+}
+
+/// A bound of a range by member: `-`, `+`, `[member` or `(member`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexBound {
+    Min,
+    Max,
+    Included(String),
+    Excluded(String),
+}
+
+type ScoredMember = (Score, String);
+
+// members with their scores, as handed out to commands
+type Members = Vec<(String, f64)>;
+
+impl ZSet {
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Set the score of `member`, returning its previous score.
+    pub fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+        // -0.0 sorts before 0.0 but redis treats them as the same score
+        let score = score + 0.0;
+        let previous = self.scores.insert(member.clone(), score);
+        if let Some(previous) = previous {
+            self.ordered.remove(&(Score(previous), member.clone()));
+        }
+        self.ordered.insert((Score(score), member));
+        previous
+    }
+
+    pub fn remove(&mut self, member: &str) -> Option<f64> {
+        let (member, score) = self.scores.remove_entry(member)?;
+        self.ordered.remove(&(Score(score), member));
+        Some(score)
+    }
+
+    /// The 0-based position of `member` in score order.
+    pub fn rank(&self, member: &str) -> Option<usize> {
+        let score = Score(self.score(member)?);
+        Some(
+            self.ordered
+                .count_while(|(s, m)| (*s, m.as_str()) < (score, member)),
+        )
+    }
+
+    /// Remove up to `count` members from `end`, returning them with their scores.
+    pub fn pop(&mut self, end: ZSetEnd, count: usize) -> Vec<(String, f64)> {
+        let mut popped = Vec::with_capacity(count.min(self.len()));
+        while popped.len() < count {
+            let next = match end {
+                ZSetEnd::Min => self.ordered.pop_first(),
+                ZSetEnd::Max => self.ordered.pop_last(),
+            };
+            let Some((score, member)) = next else {
+                break;
+            };
+            self.scores.remove(&member);
+            popped.push((member, score.0));
+        }
+        popped
+    }
+
+    /// The members and their scores in score order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&String, f64)> {
+        self.ordered.iter().map(|(score, member)| (member, score.0))
+    }
+
+    /// The members `spec` selects and their scores, in O(log N + M) where M is the number
+    /// of members returned.
+    pub fn range(&self, spec: &ZRangeSpec) -> Vec<(&String, f64)> {
+        let (start, end) = self.range_ranks(spec);
+        let (offset, count) = spec.limit.unwrap_or((0, usize::MAX));
+        // the offset of a reversed range counts from its end
+        let len = end - start;
+        let offset = offset.min(len);
+        let count = count.min(len - offset);
+        let first = if spec.rev {
+            start + len - offset - count
+        } else {
+            start + offset
+        };
+        let members = self.ordered.ranks(first, first + count);
+        let members: Box<dyn Iterator<Item = &ScoredMember>> = if spec.rev {
+            Box::new(members.rev())
+        } else {
+            Box::new(members)
+        };
+        members.map(|(score, member)| (member, score.0)).collect()
+    }
+
+    /// The number of members `spec` selects, in O(log N).
+    pub fn count(&self, spec: &ZRangeSpec) -> usize {
+        let (start, end) = self.range_ranks(spec);
+        let (offset, count) = spec.limit.unwrap_or((0, usize::MAX));
+        (end - start).saturating_sub(offset).min(count)
+    }
+
+    // the ranks `start..end` of the members `spec` selects, before applying its LIMIT
+    fn range_ranks(&self, spec: &ZRangeSpec) -> (usize, usize) {
+        let bounds = match &spec.by {
+            ZRangeBy::Rank(start, stop) => {
+                let len = self.len();
+                // the ranks of a reversed range count from the end of the set
+                return match list_range(len, *start, *stop) {
+                    Some((start, stop)) if spec.rev => (len - 1 - stop, len - start),
+                    Some((start, stop)) => (start, stop + 1),
+                    None => (0, 0),
+                };
+            }
+            ZRangeBy::Score(min, max) => score_bounds(min, max),
+            ZRangeBy::Lex(min, max) => match self.ordered.first() {
+                Some((score, _)) => lex_bounds(*score, min, max),
+                None => None,
+            },
+        };
+        let Some((lower, upper)) = bounds else {
+            return (0, 0);
+        };
+        let start = self.ordered.count_while(|member| match &lower {
+            Bound::Included(lower) => member < lower,
+            Bound::Excluded(lower) => member <= lower,
+            Bound::Unbounded => false,
+        });
+        let end = self.ordered.count_while(|member| match &upper {
+            Bound::Included(upper) => member <= upper,
+            Bound::Excluded(upper) => member < upper,
+            Bound::Unbounded => true,
+        });
+        (start, end.max(start))
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+type Bounds = (Bound<ScoredMember>, Bound<ScoredMember>);
+
+// `min..max` as bounds of (score, member) entries, None if nothing can be in the
+// range. Since the empty string sorts first, (score, "") comes before every member
+// with that score.
+fn score_bounds(min: &Bound<f64>, max: &Bound<f64>) -> Option<Bounds> {
+    let at = |score: f64| (Score(score + 0.0), String::new());
+    let lower = match *min {
+        Bound::Included(min) => Bound::Included(at(min)),
+        Bound::Excluded(f64::INFINITY) => return None,
+        Bound::Excluded(min) => Bound::Included(at((min + 0.0).next_up())),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+    let upper = match *max {
+        Bound::Included(f64::INFINITY) | Bound::Unbounded => Bound::Unbounded,
+        Bound::Included(max) => Bound::Excluded(at((max + 0.0).next_up())),
+        Bound::Excluded(max) => Bound::Excluded(at(max)),
+    };
+    Some((lower, upper))
+}
+
+// `min..max` as bounds of (score, member) entries that all have `score`,
+// None if nothing can be in the range
+fn lex_bounds(score: Score, min: &LexBound, max: &LexBound) -> Option<Bounds> {
+    let bound = |bound: &LexBound| match bound {
+        LexBound::Min | LexBound::Max => Bound::Unbounded,
+        LexBound::Included(member) => Bound::Included((score, member.clone())),
+        LexBound::Excluded(member) => Bound::Excluded((score, member.clone())),
+    };
+    match (min, max) {
+        (LexBound::Max, _) | (_, LexBound::Min) => None,
+        _ => Some((bound(min), bound(max))),
+    }
+}
+
+impl BackendInner {
+    /// Add the members with their scores, or update the scores of existing members,
+    /// as far as `options` allow. Returns the number of members added, plus the number
+    /// of members updated with CH.
+    pub fn zadd(
+        &self,
+        key: String,
+        pairs: Vec<(f64, String)>,
+        options: ZAddOptions,
+    ) -> Result<usize, BackendError> {
+        let count = self
+            .write_value(key.clone(), true, |zset: &mut ZSet| {
+                let mut count = 0;
+                for (score, member) in pairs {
+                    match zadd_one(zset, member, score, &options) {
+                        ZAddResult::Added => count += 1,
+                        ZAddResult::Updated if options.ch => count += 1,
+                        _ => {}
+                    }
+                }
+                count
+            })?
+            .unwrap_or_default();
+        self.signal_ready(&key);
+        Ok(count)
+    }
+
+    /// Add `increment` to the score of `member`, a missing member counts as 0.
+    /// Returns the new score, None if `options` prevented the update.
+    pub fn zincr_by(
+        &self,
+        key: String,
+        member: String,
+        increment: f64,
+        options: ZAddOptions,
+    ) -> Result<Option<f64>, BackendError> {
+        let score = self
+            .write_value(key.clone(), true, |zset: &mut ZSet| {
+                let score = zset.score(&member).unwrap_or_default() + increment;
+                if score.is_nan() {
+                    return Err(BackendError::ScoreNaN);
+                }
+                Ok(match zadd_one(zset, member, score, &options) {
+                    ZAddResult::Added | ZAddResult::Updated | ZAddResult::Unchanged => Some(score),
+                    ZAddResult::Skipped => None,
+                })
+            })?
+            .unwrap_or(Ok(None))?;
+        self.signal_ready(&key);
+        Ok(score)
+    }
+
+    /// Pop up to `count` members from `end` of the sorted set, None if the key doesn't exist.
+    pub fn zpop(
+        &self,
+        key: &str,
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Option<Members>, BackendError> {
+        self.write_value(key.to_string(), false, |zset: &mut ZSet| {
+            zset.pop(end, count)
+        })
+    }
+
+    /// Pop up to `count` members from `end` of the first of `keys` holding a sorted set.
+    /// Returns the key popped from and the members with their scores.
+    pub fn zmpop(
+        &self,
+        keys: &[String],
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Option<(String, Members)>, BackendError> {
+        let _guard = self.exclusive();
+        for key in keys {
+            if let Some(members) =
+                self.update_value(key.clone(), false, |zset: &mut ZSet| zset.pop(end, count))?
+            {
+                return Ok(Some((key.clone(), members)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Remove `members` from the sorted set. Returns the number of members removed.
+    pub fn zrem(&self, key: &str, members: &[String]) -> Result<usize, BackendError> {
+        Ok(self
+            .write_value(key.to_string(), false, |zset: &mut ZSet| {
+                members
+                    .iter()
+                    .filter(|member| zset.remove(member).is_some())
+                    .count()
+            })?
+            .unwrap_or_default())
+    }
+
+    pub fn zscore(&self, key: &str, member: &str) -> Result<Option<f64>, BackendError> {
+        Ok(self
+            .read_value(key, |zset: &ZSet| zset.score(member))?
+            .flatten())
+    }
+
+    pub fn zcard(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self
+            .read_value(key, |zset: &ZSet| zset.len())?
+            .unwrap_or_default())
+    }
+
+    /// The rank of `member` and its score, ranks count from the highest score
+    /// if `reverse` is set.
+    pub fn zrank(
+        &self,
+        key: &str,
+        member: &str,
+        reverse: bool,
+    ) -> Result<Option<(usize, f64)>, BackendError> {
+        Ok(self
+            .read_value(key, |zset: &ZSet| {
+                let rank = zset.rank(member)?;
+                let rank = if reverse { zset.len() - 1 - rank } else { rank };
+                Some((rank, zset.score(member)?))
+            })?
+            .flatten())
+    }
+
+    /// The members `spec` selects with their scores, see `ZSet::range`.
+    pub fn zrange(&self, key: &str, spec: &ZRangeSpec) -> Result<Vec<(String, f64)>, BackendError> {
+        Ok(self
+            .read_value(key, |zset: &ZSet| {
+                zset.range(spec)
+                    .into_iter()
+                    .map(|(member, score)| (member.clone(), score))
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// Store the members `spec` selects from `key` in `destination`, replacing whatever
+    /// it held. Returns the number of members stored.
+    pub fn zrange_store(
+        &self,
+        destination: String,
+        key: &str,
+        spec: &ZRangeSpec,
+    ) -> Result<usize, BackendError> {
+        let len = {
+            let _guard = self.exclusive();
+            let zset = match self.lookup(key).as_deref() {
+                Some(Expiring {
+                    value: Value::ZSet(zset),
+                    ..
+                }) => {
+                    let mut range = ZSet::default();
+                    for (member, score) in zset.range(spec) {
+                        range.insert(member.clone(), score);
+                    }
+                    range
+                }
+                Some(_) => return Err(BackendError::WrongType),
+                None => ZSet::default(),
+            };
+            self.store_zset(destination.clone(), zset)
+        };
+        self.signal_ready(&destination);
+        Ok(len)
+    }
+
+    /// The number of members `spec` selects, see `ZSet::count`.
+    pub fn zcount(&self, key: &str, spec: &ZRangeSpec) -> Result<usize, BackendError> {
+        Ok(self
+            .read_value(key, |zset: &ZSet| zset.count(spec))?
+            .unwrap_or_default())
+    }
+
+    /// Combine the sorted sets at `keys` with `op`, returning the members of the result
+    /// with their scores in score order. Plain sets count as sorted sets whose members
+    /// all have a score of 1.
+    pub fn zsets_combine(
+        &self,
+        op: SetOp,
+        keys: &[String],
+        options: &ZCombineOptions,
+    ) -> Result<Vec<(String, f64)>, BackendError> {
+        let _guard = self.exclusive();
+        let zset = self.with_zsets(keys, |inputs| combine(op, inputs, options))?;
+        Ok(zset
+            .iter()
+            .map(|(member, score)| (member.clone(), score))
+            .collect())
+    }
+
+    /// Like `zsets_combine`, but the result replaces whatever `destination` held.
+    /// Returns the size of the result, an empty one deletes `destination`.
+    pub fn zsets_combine_store(
+        &self,
+        op: SetOp,
+        destination: String,
+        keys: &[String],
+        options: &ZCombineOptions,
+    ) -> Result<usize, BackendError> {
+        let len = {
+            let _guard = self.exclusive();
+            let zset = self.with_zsets(keys, |inputs| combine(op, inputs, options))?;
+            self.store_zset(destination.clone(), zset)
+        };
+        self.signal_ready(&destination);
+        Ok(len)
+    }
+
+    // replace whatever `destination` held with `zset`, an empty one deletes it.
+    // Returns the size of `zset`. The caller must hold the exclusive guard.
+    fn store_zset(&self, destination: String, zset: ZSet) -> usize {
+        let len = zset.len();
+        self.db.remove(&destination);
+        if len > 0 {
+            self.db
+                .insert(destination, Expiring::new(Value::ZSet(zset)));
+        }
+        len
+    }
+
+    // run `f` on the sorted sets or sets at `keys`, None for the keys that don't exist.
+    // The caller must hold the exclusive guard, see `read_values`.
+    fn with_zsets<T>(
+        &self,
+        keys: &[String],
+        f: impl FnOnce(&[Option<Input>]) -> T,
+    ) -> Result<T, BackendError> {
+        self.read_values(
+            keys,
+            |value| Input::project(value).is_some(),
+            |values| {
+                let inputs: Vec<_> = values.iter().map(|v| v.and_then(Input::project)).collect();
+                f(&inputs)
+            },
+        )
+    }
+}
+
+impl Collection for ZSet {
+    fn project(value: &Value) -> Option<&Self> {
+        match value {
+            Value::ZSet(zset) => Some(zset),
+            _ => None,
+        }
+    }
+
+    fn project_mut(value: &mut Value) -> Option<&mut Self> {
+        match value {
+            Value::ZSet(zset) => Some(zset),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::ZSet(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ZSet::is_empty(self)
+    }
+}
+
+// an input of ZUNION, ZINTER or ZDIFF
+enum Input<'a> {
+    ZSet(&'a ZSet),
+    Set(&'a HashSet<String>),
+}
+
+impl<'a> Input<'a> {
+    fn project(value: &'a Value) -> Option<Self> {
+        match value {
+            Value::ZSet(zset) => Some(Input::ZSet(zset)),
+            Value::Set(set) => Some(Input::Set(set)),
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Input::ZSet(zset) => zset.len(),
+            Input::Set(set) => set.len(),
+        }
+    }
+
+    fn score(&self, member: &str) -> Option<f64> {
+        match self {
+            Input::ZSet(zset) => zset.score(member),
+            Input::Set(set) => set.contains(member).then_some(1.0),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&String, f64)> + '_> {
+        match self {
+            Input::ZSet(zset) => Box::new(zset.iter()),
+            Input::Set(set) => Box::new(set.iter().map(|member| (member, 1.0))),
+        }
+    }
+}
+
+impl Aggregate {
+    // like redis, a sum of infinities of opposite signs is 0 rather than NaN
+    fn apply(&self, current: f64, score: f64) -> f64 {
+        match self {
+            Aggregate::Sum => {
+                let sum = current + score;
+                if sum.is_nan() {
+                    0.0
+                } else {
+                    sum
+                }
+            }
+            Aggregate::Min => current.min(score),
+            Aggregate::Max => current.max(score),
+        }
+    }
+}
+
+fn combine(op: SetOp, inputs: &[Option<Input>], options: &ZCombineOptions) -> ZSet {
+    // 0 * inf is 0 rather than NaN, as in redis
+    let weighted = |i: usize, score: f64| {
+        let score = score * options.weights.get(i).copied().unwrap_or(1.0);
+        if score.is_nan() {
+            0.0
+        } else {
+            score
+        }
+    };
+    let mut zset = ZSet::default();
+    match op {
+        SetOp::Union => {
+            let mut scores: HashMap<&String, f64> = HashMap::new();
+            for (i, input) in inputs.iter().enumerate() {
+                for (member, score) in input.iter().flat_map(Input::iter) {
+                    let score = weighted(i, score);
+                    scores
+                        .entry(member)
+                        .and_modify(|current| *current = options.aggregate.apply(*current, score))
+                        .or_insert(score);
+                }
+            }
+            for (member, score) in scores {
+                zset.insert(member.clone(), score);
+            }
+        }
+        SetOp::Inter => {
+            // walk the smallest input, a missing one makes the intersection empty
+            let Some(inputs) = inputs
+                .iter()
+                .map(Option::as_ref)
+                .collect::<Option<Vec<_>>>()
+            else {
+                return zset;
+            };
+            let Some(smallest) = inputs.iter().min_by_key(|input| input.len()) else {
+                return zset;
+            };
+            'members: for (member, _) in smallest.iter() {
+                let mut result = None;
+                for (i, input) in inputs.iter().enumerate() {
+                    let Some(score) = input.score(member) else {
+                        continue 'members;
+                    };
+                    let score = weighted(i, score);
+                    result = Some(match result {
+                        Some(current) => options.aggregate.apply(current, score),
+                        None => score,
+                    });
+                }
+                if let Some(score) = result {
+                    zset.insert(member.clone(), score);
+                }
+            }
+        }
+        SetOp::Diff => {
+            let Some(Some(first)) = inputs.first() else {
+                return zset;
+            };
+            for (member, score) in first.iter() {
+                let others = &inputs[1..];
+                if !others
+                    .iter()
+                    .flatten()
+                    .any(|input| input.score(member).is_some())
+                {
+                    zset.insert(member.clone(), score);
+                }
+            }
+        }
+    }
+    zset
+}
+
+enum ZAddResult {
+    Added,
+    Updated,
+    // the member already had this score
+    Unchanged,
+    // the options don't allow adding or updating the member
+    Skipped,
+}
+
+fn zadd_one(zset: &mut ZSet, member: String, score: f64, options: &ZAddOptions) -> ZAddResult {
+    match zset.score(&member) {
+        Some(_) if options.condition == SetCondition::IfNotExists => ZAddResult::Skipped,
+        Some(current) if options.gt && score <= current || options.lt && score >= current => {
+            ZAddResult::Skipped
+        }
+        Some(current) if current == score => ZAddResult::Unchanged,
+        Some(_) => {
+            zset.insert(member, score);
+            ZAddResult::Updated
+        }
+        None if options.condition == SetCondition::IfExists => ZAddResult::Skipped,
+        None => {
+            zset.insert(member, score);
+            ZAddResult::Added
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zset_order() {
+        let mut zset = ZSet::default();
+        zset.insert("b".to_string(), 1.0);
+        zset.insert("a".to_string(), 1.0);
+        zset.insert("c".to_string(), f64::NEG_INFINITY);
+        zset.insert("d".to_string(), -0.0);
+        assert_eq!(zset.insert("c".to_string(), 5.0), Some(f64::NEG_INFINITY));
+
+        let members: Vec<_> = zset.iter().map(|(member, _)| member.as_str()).collect();
+        assert_eq!(members, ["d", "a", "b", "c"]);
+        assert_eq!(zset.rank("a"), Some(1));
+        assert_eq!(zset.rank("c"), Some(3));
+        assert_eq!(zset.rank("x"), None);
+
+        // -0.0 and 0.0 are the same score
+        zset.insert("e".to_string(), 0.0);
+        assert_eq!(zset.rank("e"), Some(1));
+        assert_eq!(zset.remove("a"), Some(1.0));
+        assert_eq!(zset.rank("b"), Some(2));
+        assert_eq!(zset.len(), 4);
+    }
+
+    #[test]
+    fn test_zset_range() {
+        let mut zset = ZSet::default();
+        for (member, score) in [
+            ("a", 1.0),
+            ("b", 2.0),
+            ("c", 2.0),
+            ("", 3.0),
+            ("d", f64::INFINITY),
+        ] {
+            zset.insert(member.to_string(), score);
+        }
+        // every range also checks that ZSet::count agrees with it
+        let range = |by, rev, limit| -> Vec<String> {
+            let spec = ZRangeSpec { by, rev, limit };
+            let members: Vec<_> = zset
+                .range(&spec)
+                .into_iter()
+                .map(|(member, _)| member.clone())
+                .collect();
+            assert_eq!(zset.count(&spec), members.len());
+            members
+        };
+
+        assert_eq!(range(ZRangeBy::Rank(1, -2), false, None), ["b", "c", ""]);
+        assert_eq!(range(ZRangeBy::Rank(0, 1), true, None), ["d", ""]);
+        assert_eq!(range(ZRangeBy::Rank(-1, -1), false, None), ["d"]);
+        assert!(range(ZRangeBy::Rank(3, 1), false, None).is_empty());
+
+        use Bound::*;
+        let by_score = |min, max| ZRangeBy::Score(min, max);
+        assert_eq!(
+            range(by_score(Excluded(1.0), Included(3.0)), false, None),
+            ["b", "c", ""]
+        );
+        assert_eq!(
+            range(by_score(Included(2.0), Excluded(3.0)), true, None),
+            ["c", "b"]
+        );
+        assert_eq!(
+            range(
+                by_score(Included(f64::NEG_INFINITY), Included(f64::INFINITY)),
+                false,
+                Some((3, 5))
+            ),
+            ["", "d"]
+        );
+        assert_eq!(
+            range(by_score(Included(2.0), Unbounded), true, Some((1, 2))),
+            ["", "c"]
+        );
+        assert!(range(by_score(Excluded(f64::INFINITY), Unbounded), false, None).is_empty());
+        assert!(range(by_score(Excluded(2.0), Excluded(2.0)), false, None).is_empty());
+        assert!(range(by_score(Included(3.0), Included(1.0)), false, None).is_empty());
+
+        let mut lex = ZSet::default();
+        for member in ["a", "b", "c", "d"] {
+            lex.insert(member.to_string(), 0.0);
+        }
+        let range = |min, max, rev| -> Vec<String> {
+            let spec = ZRangeSpec {
+                by: ZRangeBy::Lex(min, max),
+                rev,
+                limit: None,
+            };
+            let members: Vec<_> = lex
+                .range(&spec)
+                .into_iter()
+                .map(|(member, _)| member.clone())
+                .collect();
+            assert_eq!(lex.count(&spec), members.len());
+            members
+        };
+        let included = |member: &str| LexBound::Included(member.to_string());
+        let excluded = |member: &str| LexBound::Excluded(member.to_string());
+        assert_eq!(range(LexBound::Min, excluded("c"), false), ["a", "b"]);
+        assert_eq!(range(included("b"), LexBound::Max, true), ["d", "c", "b"]);
+        assert_eq!(range(excluded("a"), included("c"), false), ["b", "c"]);
+        assert!(range(LexBound::Max, LexBound::Min, false).is_empty());
+        assert!(range(excluded("b"), excluded("b"), false).is_empty());
+        assert!(range(included("c"), included("a"), false).is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use crate::{
+    backend::{Backend, Blocked, ServeFn},
+    resp::{RespFrame, RespNull, RespNullArray},
+};
+
+use super::{Command, CommandExecutor, Session};
+
+/// A command that waits for one of its keys to be able to serve it.
+pub trait BlockingCommand {
+    /// The keys the command waits on, in the order they are tried.
+    fn keys(&self) -> Vec<String>;
+    /// None waits forever.
+    fn timeout(&self) -> Option<Duration>;
+    fn serve(&self) -> ServeFn;
+    /// The reply when no key serves the command before it times out.
+    fn null_reply(&self) -> RespFrame {
+        RespFrame::Null(RespNull)
+    }
+}
+
+impl Command {
+    /// Execute the command for the client of `session`. A blocking command waits
+    /// here until one of its keys serves it or it times out.
+    pub async fn run(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        match self {
+            Command::Hello(cmd) => cmd.apply(session),
+            Command::BLPop(cmd) => block(cmd, backend).await,
+            Command::BRPop(cmd) => block(cmd, backend).await,
+            Command::BLMove(cmd) => block(cmd, backend).await,
+            Command::BLMPop(cmd) => block(cmd, backend).await,
+            Command::BZPopMin(cmd) => block(cmd, backend).await,
+            Command::BZPopMax(cmd) => block(cmd, backend).await,
+            Command::BZMPop(cmd) => block(cmd, backend).await,
+            cmd => cmd.execute(backend),
+        }
+    }
+}
+
+async fn block(cmd: impl BlockingCommand, backend: &Backend) -> RespFrame {
+    match backend.block(cmd.keys(), cmd.serve()) {
+        Ok(Blocked::Ready(reply)) => reply,
+        Ok(Blocked::Parked(waiter)) => waiter
+            .wait(cmd.timeout())
+            .await
+            .unwrap_or_else(|| cmd.null_reply()),
+        Err(e) => e.into(),
+    }
+}
+
+/// Serve a blocking command without blocking, its null reply if none of its keys can
+/// serve it.
+pub(super) fn serve_now(cmd: &impl BlockingCommand, backend: &Backend) -> RespFrame {
+    let serve = cmd.serve();
+    for key in cmd.keys() {
+        match serve(backend, &key) {
+            Ok(Some(reply)) => return reply,
+            Ok(None) => {}
+            Err(e) => return e.into(),
+        }
+    }
+    cmd.null_reply()
+}
+
+// like redis, the pops reply a null array, which RESP2 tells apart from a null string
+pub(super) fn null_array() -> RespFrame {
+    RespNullArray.into()
+}
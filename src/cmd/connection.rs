@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    backend::Backend,
+    resp::{BulkString, Protocol, RespArray, RespFrame, RespMap, SimpleError},
+};
+
+use super::{extract_args, extract_i64, extract_string, CommandError, CommandExecutor, Hello};
+
+/// The state of a client connection that outlives a single command.
+#[derive(Debug)]
+pub struct Session {
+    id: u64,
+    protocol: Protocol,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            protocol: Protocol::default(),
+        }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hello {
+    /// Switch the connection to the requested protocol and describe the server.
+    pub fn apply(self, session: &mut Session) -> RespFrame {
+        if let Some(protover) = self.protover {
+            match Protocol::try_from(protover) {
+                Ok(protocol) => session.protocol = protocol,
+                Err(_) => {
+                    return RespFrame::Error(SimpleError(
+                        "NOPROTO unsupported protocol version".to_string(),
+                    ))
+                }
+            }
+        }
+        let bulk = |s: &str| -> RespFrame { BulkString(s.into()).into() };
+        let mut reply = RespMap(HashMap::new());
+        reply.insert("server".to_string(), bulk("redis"));
+        reply.insert("version".to_string(), bulk(env!("CARGO_PKG_VERSION")));
+        reply.insert(
+            "proto".to_string(),
+            RespFrame::Integer(session.protocol.version()),
+        );
+        reply.insert("id".to_string(), RespFrame::Integer(session.id as i64));
+        reply.insert("mode".to_string(), bulk("standalone"));
+        reply.insert("role".to_string(), bulk("master"));
+        reply.insert("modules".to_string(), RespArray(vec![]).into());
+        reply.into()
+    }
+}
+
+// outside of a connection there is no protocol to switch, HELLO only describes the server
+impl CommandExecutor for Hello {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        self.apply(&mut Session::new())
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // hello [protover], authentication and client names are not supported
+        let mut args = extract_args(value, 1)?.into_iter();
+        let protover = match args.next() {
+            Some(protover) => Some(extract_i64(Some(protover)).map_err(|_| {
+                CommandError::InvalidArgument(
+                    "Protocol version is not an integer or out of range".to_string(),
+                )
+            })?),
+            None => None,
+        };
+        if let Some(option) = args.next() {
+            return Err(CommandError::InvalidArgument(format!(
+                "Syntax error in HELLO option '{}'",
+                extract_string(Some(option))?
+            )));
+        }
+        Ok(Hello { protover })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resp::RespDecode;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_hello_switches_protocol() -> Result<()> {
+        let mut session = Session::new();
+        assert_eq!(session.protocol(), Protocol::Resp2);
+
+        let reply = Hello { protover: Some(3) }.apply(&mut session);
+        assert_eq!(session.protocol(), Protocol::Resp3);
+        let RespFrame::Map(reply) = reply else {
+            panic!("expected a map, got {:?}", reply);
+        };
+        assert_eq!(reply.get("proto"), Some(&RespFrame::Integer(3)));
+        assert_eq!(
+            reply.get("id"),
+            Some(&RespFrame::Integer(session.id as i64))
+        );
+
+        // without a version the protocol is kept
+        let RespFrame::Map(reply) = (Hello { protover: None }).apply(&mut session) else {
+            panic!("expected a map");
+        };
+        assert_eq!(reply.get("proto"), Some(&RespFrame::Integer(3)));
+
+        let reply = Hello { protover: Some(4) }.apply(&mut session);
+        assert_eq!(
+            reply,
+            RespFrame::Error(SimpleError(
+                "NOPROTO unsupported protocol version".to_string()
+            ))
+        );
+        assert_eq!(session.protocol(), Protocol::Resp3);
+
+        let cases = [
+            (
+                "*2\r\n$5\r\nhello\r\n$5\r\nthree\r\n",
+                "Protocol version is not an integer or out of range",
+            ),
+            (
+                "*4\r\n$5\r\nhello\r\n$1\r\n3\r\n$7\r\nsetname\r\n$3\r\nfoo\r\n",
+                "Syntax error in HELLO option 'setname'",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = Hello::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message);
+        }
+        Ok(())
+    }
+}
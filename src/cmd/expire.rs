@@ -0,0 +1,418 @@
+use crate::{
+    backend::{Backend, ExpireCondition},
+    resp::{RespArray, RespFrame, SimpleError},
+};
+
+use super::{
+    extract_args, extract_i64, extract_string, CommandError, CommandExecutor, Expire, ExpireAt,
+    PExpire, PExpireAt, PTtl, Persist, Ttl,
+};
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(backend.now_ms()));
+        expire_generic(backend, &self.key, at, self.condition, "expire")
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(backend.now_ms());
+        expire_generic(backend, &self.key, at, self.condition, "pexpire")
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.checked_mul(1000);
+        expire_generic(backend, &self.key, at, self.condition, "expireat")
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_generic(
+            backend,
+            &self.key,
+            Some(self.timestamp_ms),
+            self.condition,
+            "pexpireat",
+        )
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expire_at(&self.key) {
+            None => RespFrame::Integer(-2),
+            Some(None) => RespFrame::Integer(-1),
+            Some(Some(at)) => RespFrame::Integer((at - backend.now_ms() + 500) / 1000),
+        }
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expire_at(&self.key) {
+            None => RespFrame::Integer(-2),
+            Some(None) => RespFrame::Integer(-1),
+            Some(Some(at)) => RespFrame::Integer(at - backend.now_ms()),
+        }
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+// `at` is None when computing the deadline overflowed
+fn expire_generic(
+    backend: &Backend,
+    key: &str,
+    at: Option<i64>,
+    condition: ExpireCondition,
+    name: &str,
+) -> RespFrame {
+    match at {
+        Some(at) => RespFrame::Integer(backend.expire(key, at, condition) as i64),
+        None => RespFrame::Error(SimpleError(format!(
+            "ERR invalid expire time in '{}' command",
+            name
+        ))),
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition) = parse_expire_args(value)?;
+        Ok(Expire {
+            key,
+            seconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition) = parse_expire_args(value)?;
+        Ok(PExpire {
+            key,
+            milliseconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value)?;
+        Ok(ExpireAt {
+            key,
+            timestamp,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp_ms, condition) = parse_expire_args(value)?;
+        Ok(PExpireAt {
+            key,
+            timestamp_ms,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Ttl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PTtl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Persist {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+// <cmd> key time [NX | XX | GT | LT]
+fn parse_expire_args(value: RespArray) -> Result<(String, i64, ExpireCondition), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let time = extract_i64(args.next())?;
+
+    let mut condition = ExpireCondition::default();
+    for arg in args {
+        let option = extract_string(Some(arg))?;
+        match option.to_ascii_lowercase().as_str() {
+            "nx" => condition.nx = true,
+            "xx" => condition.xx = true,
+            "gt" => condition.gt = true,
+            "lt" => condition.lt = true,
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unsupported option {}",
+                    option
+                )))
+            }
+        }
+    }
+    if condition.nx && (condition.xx || condition.gt || condition.lt) {
+        return Err(CommandError::InvalidArgument(
+            "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    if condition.gt && condition.lt {
+        return Err(CommandError::InvalidArgument(
+            "GT and LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    Ok((key, time, condition))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        backend::MockClock,
+        cmd::{Get, HGet, HGetAll, HSet, Set},
+        resp::{BulkString, RespDecode},
+    };
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn set(backend: &Backend, key: &str) {
+        Set {
+            key: key.to_string(),
+            value: RespFrame::BulkString(BulkString("world".into())),
+            options: Default::default(),
+        }
+        .execute(backend);
+    }
+
+    #[test]
+    fn test_expire_from_resp_array() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nnx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Expire = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.seconds, 10);
+        assert!(result.condition.nx);
+
+        let mut buf = BytesMut::from(
+            "*5\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nnx\r\n$2\r\nxx\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Expire, _> = frame.try_into();
+        assert!(result.is_err());
+
+        let mut buf = BytesMut::from("*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Expire, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_ttl_persist_commands() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let backend = Backend::with_clock(clock.clone());
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+
+        set(&backend, "hello");
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        clock.advance(2_400);
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(8));
+        let cmd = PTtl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(7_600));
+
+        let cmd = Persist {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Persist {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = PExpireAt {
+            key: "hello".to_string(),
+            timestamp_ms: 1_003_000,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        clock.advance(600);
+        let cmd = Get {
+            key: "hello".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Null(crate::resp::RespNull)
+        );
+        assert!(backend.db.is_empty());
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_conditions() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let backend = Backend::with_clock(clock);
+        set(&backend, "hello");
+
+        let xx = ExpireCondition {
+            xx: true,
+            ..Default::default()
+        };
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 1_000,
+            condition: xx,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let nx = ExpireCondition {
+            nx: true,
+            ..Default::default()
+        };
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 1_000,
+            condition: nx,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let gt = ExpireCondition {
+            gt: true,
+            ..Default::default()
+        };
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 500,
+            condition: gt,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let lt = ExpireCondition {
+            lt: true,
+            ..Default::default()
+        };
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 500,
+            condition: lt,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.expire_at("hello"), Some(Some(1_000_500)));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: i64::MAX,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError(
+                "ERR invalid expire time in 'expire' command".to_string()
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_hash() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let backend = Backend::with_clock(clock.clone());
+        HSet {
+            key: "map".to_string(),
+            pairs: vec![(
+                "hello".to_string(),
+                RespFrame::BulkString(BulkString("world".into())),
+            )],
+        }
+        .execute(&backend);
+
+        let cmd = ExpireAt {
+            key: "map".to_string(),
+            timestamp: 1_001,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        clock.advance(1_000);
+        let cmd = HGet {
+            key: "map".to_string(),
+            field: "hello".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Null(crate::resp::RespNull)
+        );
+        let cmd = HGetAll {
+            key: "map".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray(vec![]).into());
+        assert!(backend.db.is_empty());
+        Ok(())
+    }
+}
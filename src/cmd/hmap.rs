@@ -1,50 +1,304 @@
-use std::collections::HashMap;
-
-use crate::resp::{RespArray, RespFrame, RespMap, RespNull};
+use crate::{
+    backend::{Backend, BackendError, ExpireCondition, SetTtl},
+    resp::{encode::format_double, BulkString, RespArray, RespFrame, RespMap, RespNull},
+};
 
 use super::{
-    extract_args, validate_command, CommandError, CommandExecutor, HGet, HGetAll, HSet, RESP_OK,
+    extract_args, extract_cursor, extract_f64, extract_i64, extract_pairs, extract_random_count,
+    extract_scan_count, extract_string, extract_strings, integer_reply,
+    map::{invalid_expire_time, parse_expiry},
+    CommandError, CommandExecutor, HDel, HExists, HExpire, HGet, HGetAll, HGetEx, HIncrBy,
+    HIncrByFloat, HKeys, HLen, HMGet, HMSet, HPExpire, HPersist, HRandField, HScan, HSet, HSetNx,
+    HStrlen, HTtl, HVals, RESP_OK, SCAN_DEFAULT_COUNT,
 };
 
 impl CommandExecutor for HGet {
-    fn execute(self, backend: &crate::backend::Backend) -> RespFrame {
+    fn execute(self, backend: &Backend) -> RespFrame {
         match backend.hget(&self.key, &self.field) {
-            Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for HSet {
-    fn execute(self, backend: &crate::backend::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hmset(self.key, self.pairs))
     }
 }
 
 impl CommandExecutor for HGetAll {
-    fn execute(self, backend: &crate::backend::Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let hmap = backend.hgetall(&self.key);
 
         match hmap {
-            Some(hmap) => {
-                let mut map = RespMap(HashMap::new());
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    map.insert(key, v.value().clone());
-                }
-                map.into()
+            Ok(Some(hmap)) => RespMap(hmap).into(),
+            Ok(None) => RespArray(vec![]).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hdel(&self.key, &self.fields))
+    }
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hexists(&self.key, &self.field))
+    }
+}
+
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hlen(&self.key))
+    }
+}
+
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hkeys(&self.key) {
+            Ok(fields) => RespArray(
+                fields
+                    .into_iter()
+                    .map(|field| BulkString(field.into_bytes()).into())
+                    .collect(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hvals(&self.key) {
+            Ok(values) => RespArray(values).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HMGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hmget(&self.key, &self.fields) {
+            Ok(values) => RespArray(
+                values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(RespFrame::Null(RespNull)))
+                    .collect(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HMSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hmset(self.key, self.pairs) {
+            Ok(_) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hsetnx(self.key, self.field, self.value))
+    }
+}
+
+impl CommandExecutor for HStrlen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hstrlen(&self.key, &self.field))
+    }
+}
+
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.hincr_by(self.key, self.field, self.increment))
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincr_by_float(self.key, self.field, self.increment) {
+            Ok(value) => BulkString(format_double(value).into_bytes()).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HRandField {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let pairs = match backend.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(pairs) => pairs,
+            Err(e) => return e.into(),
+        };
+        if self.count.is_none() {
+            return match pairs.into_iter().next() {
+                Some((field, _)) => BulkString(field.into_bytes()).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+        let mut reply = Vec::with_capacity(pairs.len() * 2);
+        for (field, value) in pairs {
+            reply.push(BulkString(field.into_bytes()).into());
+            if self.with_values {
+                reply.push(value);
+            }
+        }
+        RespArray(reply).into()
+    }
+}
+
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, pairs) =
+            match backend.hscan(&self.key, self.cursor, self.count, self.pattern.as_deref()) {
+                Ok(result) => result,
+                Err(e) => return e.into(),
+            };
+        let mut items = Vec::with_capacity(pairs.len() * 2);
+        for (field, value) in pairs {
+            items.push(BulkString(field.into_bytes()).into());
+            if !self.novalues {
+                items.push(value);
             }
-            None => RespArray(vec![]).into(),
         }
+        RespArray(vec![
+            BulkString(cursor.to_string().into_bytes()).into(),
+            RespArray(items).into(),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for HExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(backend.now_ms()));
+        hexpire_generic(
+            backend,
+            &self.key,
+            &self.fields,
+            at,
+            self.condition,
+            "hexpire",
+        )
+    }
+}
+
+impl CommandExecutor for HPExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(backend.now_ms());
+        hexpire_generic(
+            backend,
+            &self.key,
+            &self.fields,
+            at,
+            self.condition,
+            "hpexpire",
+        )
+    }
+}
+
+impl CommandExecutor for HTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let now = backend.now_ms();
+        field_results(backend.hexpire_at(&self.key, &self.fields), |at| match at {
+            None => -2,
+            Some(None) => -1,
+            Some(Some(at)) => (at - now + 500) / 1000,
+        })
+    }
+}
+
+impl CommandExecutor for HPersist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        field_results(
+            backend.hpersist(&self.key, &self.fields),
+            |persisted| match persisted {
+                None => -2,
+                Some(false) => -1,
+                Some(true) => 1,
+            },
+        )
+    }
+}
+
+impl CommandExecutor for HGetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.expiry {
+            _ if self.persist => Some(SetTtl::Clear),
+            None => Some(SetTtl::Keep),
+            Some(expiry) => expiry.to_ttl(backend.now_ms()),
+        };
+        let Some(ttl) = ttl else {
+            return invalid_expire_time("hgetex");
+        };
+        match backend.hgetex(&self.key, &self.fields, ttl) {
+            Ok(values) => RespArray(
+                values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(RespFrame::Null(RespNull)))
+                    .collect(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// `at` is None when computing the deadline overflowed. Each field is answered with
+// -2 if it doesn't exist, 0 if the condition is not met, 1 if its deadline was set
+// and 2 if it was deleted because the deadline is in the past.
+fn hexpire_generic(
+    backend: &Backend,
+    key: &str,
+    fields: &[String],
+    at: Option<i64>,
+    condition: ExpireCondition,
+    name: &str,
+) -> RespFrame {
+    let Some(at) = at else {
+        return invalid_expire_time(name);
+    };
+    let now = backend.now_ms();
+    field_results(
+        backend.hexpire(key, fields, at, condition),
+        |set| match set {
+            None => -2,
+            Some(false) => 0,
+            Some(true) if at <= now => 2,
+            Some(true) => 1,
+        },
+    )
+}
+
+// one integer per field
+fn field_results<T>(result: Result<Vec<T>, BackendError>, f: impl Fn(T) -> i64) -> RespFrame {
+    match result {
+        Ok(results) => RespArray(
+            results
+                .into_iter()
+                .map(|result| RespFrame::Integer(f(result)))
+                .collect(),
+        )
+        .into(),
+        Err(e) => e.into(),
     }
 }
 
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hget"], 2)?;
-
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => Ok(HGet {
@@ -61,8 +315,6 @@ impl TryFrom<RespArray> for HGet {
 impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
-
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(HGetAll {
@@ -76,28 +328,339 @@ impl TryFrom<RespArray> for HGetAll {
 impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        // hset key field value [field value ...]
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HSet {
+            key: extract_string(args.next())?,
+            pairs: extract_pairs(args.collect(), "hset")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HDel {
+            key: extract_string(args.next())?,
+            fields: extract_strings(args.collect())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HExists {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value: value,
-                })
+        Ok(HLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HKeys {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HVals {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HMGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HMGet {
+            key: extract_string(args.next())?,
+            fields: extract_strings(args.collect())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HMSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HMSet {
+            key: extract_string(args.next())?,
+            pairs: extract_pairs(args.collect(), "hmset")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let field = extract_string(args.next())?;
+        let Some(value) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(HSetNx { key, field, value })
+    }
+}
+
+impl TryFrom<RespArray> for HStrlen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HStrlen {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrBy {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrByFloat {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_f64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // hrandfield key [count [WITHVALUES]]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(count) => Some(extract_random_count(Some(count))?),
+            None => None,
+        };
+        let with_values = match args.next() {
+            Some(arg) if args.len() == 0 => {
+                if !extract_string(Some(arg))?.eq_ignore_ascii_case("withvalues") {
+                    return Err(CommandError::InvalidArgument("syntax error".to_string()));
+                }
+                true
+            }
+            Some(_) => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            None => false,
+        };
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // hscan key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut hscan = HScan {
+            key: extract_string(args.next())?,
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: SCAN_DEFAULT_COUNT,
+            novalues: false,
+        };
+
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            match option.as_str() {
+                "novalues" => hscan.novalues = true,
+                "match" if args.len() > 0 => hscan.pattern = Some(extract_string(args.next())?),
+                "count" if args.len() > 0 => hscan.count = extract_scan_count(args.next())?,
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(hscan)
+    }
+}
+
+impl TryFrom<RespArray> for HExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition, fields) = parse_hexpire_args(value)?;
+        Ok(HExpire {
+            key,
+            seconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition, fields) = parse_hexpire_args(value)?;
+        Ok(HPExpire {
+            key,
+            milliseconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // httl key FIELDS numfields field [field ...]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let keyword = extract_string(args.next())?;
+        Ok(HTtl {
+            key,
+            fields: extract_fields(&keyword, args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPersist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // hpersist key FIELDS numfields field [field ...]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let keyword = extract_string(args.next())?;
+        Ok(HPersist {
+            key,
+            fields: extract_fields(&keyword, args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HGetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // hgetex key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+        //   PXAT unix-time-milliseconds | PERSIST] FIELDS numfields field [field ...]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut hgetex = HGetEx {
+            key: extract_string(args.next())?,
+            expiry: None,
+            persist: false,
+            fields: Vec::new(),
+        };
+        loop {
+            let option = extract_string(args.next())?.to_ascii_lowercase();
+            let unset = hgetex.expiry.is_none() && !hgetex.persist;
+            match option.as_str() {
+                "persist" if unset => hgetex.persist = true,
+                "ex" | "px" | "exat" | "pxat" if unset => {
+                    hgetex.expiry = Some(parse_expiry(&option, args.next(), "hgetex")?);
+                }
+                _ => {
+                    hgetex.fields = extract_fields(&option, args)?;
+                    return Ok(hgetex);
+                }
             }
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key, field of value".to_string(),
-            )),
         }
     }
 }
 
+// <cmd> key time [NX | XX | GT | LT] FIELDS numfields field [field ...]
+fn parse_hexpire_args(
+    value: RespArray,
+) -> Result<(String, i64, ExpireCondition, Vec<String>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let time = extract_i64(args.next())?;
+    if time < 0 {
+        return Err(CommandError::InvalidArgument(
+            "invalid expire time, must be >= 0".to_string(),
+        ));
+    }
+
+    let mut condition = ExpireCondition::default();
+    let mut keyword = extract_string(args.next())?;
+    match keyword.to_ascii_lowercase().as_str() {
+        "nx" => condition.nx = true,
+        "xx" => condition.xx = true,
+        "gt" => condition.gt = true,
+        "lt" => condition.lt = true,
+        _ => return Ok((key, time, condition, extract_fields(&keyword, args)?)),
+    }
+    keyword = extract_string(args.next())?;
+    let fields = extract_fields(&keyword, args)?;
+    Ok((key, time, condition, fields))
+}
+
+// FIELDS numfields field [field ...], which ends the arguments of the commands
+// working on the deadlines of hash fields
+fn extract_fields(
+    keyword: &str,
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<Vec<String>, CommandError> {
+    if !keyword.eq_ignore_ascii_case("fields") {
+        return Err(CommandError::InvalidArgument(
+            "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+        ));
+    }
+    let numfields = extract_i64(args.next())
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            CommandError::InvalidArgument(
+                "Parameter `numFields` should be greater than 0".to_string(),
+            )
+        })?;
+    let fields = extract_strings(args.collect())?;
+    if fields.len() as i64 != numfields {
+        return Err(CommandError::InvalidArgument(
+            "The `numfields` parameter must match the number of arguments".to_string(),
+        ));
+    }
+    Ok(fields)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
     use crate::{
-        backend::Backend,
-        resp::{BulkString, RespDecode},
+        backend::MockClock,
+        cmd::SetExpiry,
+        resp::{RespDecode, SimpleError},
+        test_util::{bulk, command},
     };
 
     use super::*;
@@ -134,12 +697,32 @@ mod tests {
 
         let result: HSet = frame.try_into()?;
         assert_eq!(result.key, "map");
-        assert_eq!(result.field, "hello");
         assert_eq!(
-            result.value,
-            RespFrame::BulkString(BulkString("world".into()))
+            result.pairs,
+            vec![(
+                "hello".to_string(),
+                RespFrame::BulkString(BulkString("world".into()))
+            )]
+        );
+
+        let mut buf = BytesMut::from(
+            "*6\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HSet = frame.try_into()?;
+        assert_eq!(
+            result.pairs,
+            vec![("a".to_string(), bulk("1")), ("b".to_string(), bulk("2"))]
         );
 
+        let mut buf =
+            BytesMut::from("*5\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = HSet::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'hset' command"
+        );
         Ok(())
     }
 
@@ -148,18 +731,30 @@ mod tests {
         let backend = Backend::default();
         let cmd = HSet {
             key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(BulkString("world".into())),
+            pairs: vec![(
+                "hello".to_string(),
+                RespFrame::BulkString(BulkString("world".into())),
+            )],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
+        // only the new fields are counted
         let cmd = HSet {
             key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(BulkString("world1".into())),
+            pairs: vec![
+                (
+                    "hello".to_string(),
+                    RespFrame::BulkString(BulkString("world".into())),
+                ),
+                (
+                    "hello1".to_string(),
+                    RespFrame::BulkString(BulkString("world1".into())),
+                ),
+            ],
         };
-        cmd.execute(&backend);
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HGet {
             key: "map".to_string(),
@@ -184,4 +779,533 @@ mod tests {
         assert_eq!(result, expected.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hmset_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*6\r\n$5\r\nhmset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HMSet = frame.try_into()?;
+        assert_eq!(result.key, "map");
+        assert_eq!(
+            result.pairs,
+            vec![("a".to_string(), bulk("1")), ("b".to_string(), bulk("2"))]
+        );
+
+        let mut buf =
+            BytesMut::from("*5\r\n$5\r\nhmset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = HMSet::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'hmset' command"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_field_commands() {
+        let backend = Backend::default();
+        let key = || "map".to_string();
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect();
+
+        let cmd = HMSet {
+            key: key(),
+            pairs: vec![
+                ("a".to_string(), bulk("1")),
+                ("b".to_string(), bulk("22")),
+                ("c".to_string(), bulk("333")),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(HLen { key: key() }.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = HSetNx {
+            key: key(),
+            field: "a".to_string(),
+            value: bulk("x"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = HSetNx {
+            key: key(),
+            field: "d".to_string(),
+            value: bulk("4444"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = HMGet {
+            key: key(),
+            fields: fields(&["a", "nosuchfield", "d"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![bulk("1"), RespFrame::Null(RespNull), bulk("4444")]).into()
+        );
+        let cmd = HMGet {
+            key: "nosuchkey".to_string(),
+            fields: fields(&["a"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![RespFrame::Null(RespNull)]).into()
+        );
+
+        let cmd = HExists {
+            key: key(),
+            field: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = HStrlen {
+            key: key(),
+            field: "c".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        let cmd = HStrlen {
+            key: key(),
+            field: "nosuchfield".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let RespFrame::Array(keys) = HKeys { key: key() }.execute(&backend) else {
+            panic!("HKEYS must reply an array");
+        };
+        let mut keys = keys.0;
+        keys.sort_by_key(|k| match k {
+            RespFrame::BulkString(k) => k.0.clone(),
+            _ => panic!("fields must be bulk strings"),
+        });
+        assert_eq!(keys, vec![bulk("a"), bulk("b"), bulk("c"), bulk("d")]);
+        let RespFrame::Array(values) = HVals { key: key() }.execute(&backend) else {
+            panic!("HVALS must reply an array");
+        };
+        assert_eq!(values.len(), 4);
+    }
+
+    #[test]
+    fn test_hdel_removes_empty_hash() {
+        let backend = Backend::default();
+        backend
+            .hmset(
+                "map".to_string(),
+                vec![("a".to_string(), bulk("1")), ("b".to_string(), bulk("2"))],
+            )
+            .unwrap();
+
+        let cmd = HDel {
+            key: "map".to_string(),
+            fields: vec!["a".to_string(), "nosuchfield".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("map"), Some("hash"));
+
+        let cmd = HDel {
+            key: "map".to_string(),
+            fields: vec!["b".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("map"), None);
+        assert_eq!(
+            HLen {
+                key: "map".to_string()
+            }
+            .execute(&backend),
+            RespFrame::Integer(0)
+        );
+
+        backend.set("s".to_string(), bulk("v"));
+        let cmd = HDel {
+            key: "s".to_string(),
+            fields: vec!["a".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_hincrby_command() -> Result<()> {
+        let backend = Backend::default();
+        let hincrby = |field: &str, increment| {
+            HIncrBy {
+                key: "counters".to_string(),
+                field: field.to_string(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hincrby("a", 5), RespFrame::Integer(5));
+        assert_eq!(hincrby("a", -7), RespFrame::Integer(-2));
+        assert_eq!(backend.hget("counters", "a")?, Some(bulk("-2")));
+
+        backend.hset(
+            "counters".to_string(),
+            "max".to_string(),
+            bulk(&i64::MAX.to_string()),
+        )?;
+        assert_eq!(
+            hincrby("max", 1),
+            RespFrame::Error(SimpleError(
+                "ERR increment or decrement would overflow".to_string()
+            ))
+        );
+        backend.hset("counters".to_string(), "s".to_string(), bulk("abc"))?;
+        assert_eq!(
+            hincrby("s", 1),
+            RespFrame::Error(SimpleError("ERR hash value is not an integer".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrbyfloat_command() -> Result<()> {
+        let backend = Backend::default();
+        let hincrbyfloat = |field: &str, increment| {
+            HIncrByFloat {
+                key: "h".to_string(),
+                field: field.to_string(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hincrbyfloat("f", 10.5), bulk("10.5"));
+        assert_eq!(hincrbyfloat("f", 0.1), bulk("10.6"));
+        assert_eq!(backend.hget("h", "f")?, Some(bulk("10.6")));
+        assert_eq!(
+            hincrbyfloat("f", f64::INFINITY),
+            RespFrame::Error(SimpleError(
+                "ERR increment would produce NaN or Infinity".to_string()
+            ))
+        );
+        backend.hset("h".to_string(), "s".to_string(), bulk("abc"))?;
+        assert_eq!(
+            hincrbyfloat("s", 1.0),
+            RespFrame::Error(SimpleError("ERR hash value is not a float".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*4\r\n$10\r\nhrandfield\r\n$1\r\nh\r\n$2\r\n-5\r\n$10\r\nWITHVALUES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HRandField = frame.try_into()?;
+        assert_eq!(result.count, Some(-5));
+        assert!(result.with_values);
+
+        let mut buf =
+            BytesMut::from("*4\r\n$10\r\nhrandfield\r\n$1\r\nh\r\n$1\r\n5\r\n$3\r\nfoo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = HRandField::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "syntax error");
+
+        let frame = command(&["hrandfield", "h", "-100000000000"]);
+        let err = HRandField::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "value is out of range");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_command() -> Result<()> {
+        let backend = Backend::default();
+        let pairs = (0..5)
+            .map(|i| (format!("f{}", i), bulk(&format!("v{}", i))))
+            .collect();
+        backend.hmset("h".to_string(), pairs)?;
+        let hrandfield = |key: &str, count, with_values| {
+            HRandField {
+                key: key.to_string(),
+                count,
+                with_values,
+            }
+            .execute(&backend)
+        };
+
+        let RespFrame::BulkString(field) = hrandfield("h", None, false) else {
+            panic!("HRANDFIELD without count must reply a bulk string");
+        };
+        assert!(field.starts_with(b"f"));
+        assert_eq!(
+            hrandfield("nosuchkey", None, false),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            hrandfield("nosuchkey", Some(3), false),
+            RespArray(vec![]).into()
+        );
+
+        // a positive count returns distinct fields, at most all of them
+        let RespFrame::Array(fields) = hrandfield("h", Some(10), false) else {
+            panic!("HRANDFIELD with count must reply an array");
+        };
+        let mut fields = fields.0;
+        fields.sort_by_key(|f| format!("{:?}", f));
+        fields.dedup();
+        assert_eq!(fields.len(), 5);
+        let RespFrame::Array(pairs) = hrandfield("h", Some(100_000_000_000), true) else {
+            panic!("HRANDFIELD with count must reply an array");
+        };
+        assert_eq!(pairs.len(), 10);
+
+        // a negative count may repeat fields
+        let RespFrame::Array(fields) = hrandfield("h", Some(-20), true) else {
+            panic!("HRANDFIELD with count must reply an array");
+        };
+        assert_eq!(fields.len(), 40);
+        for pair in fields.chunks(2) {
+            let (RespFrame::BulkString(field), RespFrame::BulkString(value)) = (&pair[0], &pair[1])
+            else {
+                panic!("fields and values must be bulk strings");
+            };
+            assert_eq!(field[1..], value[1..]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*8\r\n$5\r\nhscan\r\n$1\r\nh\r\n$1\r\n7\r\n$5\r\nMATCH\r\n$2\r\nf*\r\n$5\r\nCOUNT\r\n$2\r\n20\r\n$8\r\nNOVALUES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HScan = frame.try_into()?;
+        assert_eq!(result.key, "h");
+        assert_eq!(result.cursor, 7);
+        assert_eq!(result.pattern.as_deref(), Some("f*"));
+        assert_eq!(result.count, 20);
+        assert!(result.novalues);
+
+        let mut buf = BytesMut::from("*4\r\n$5\r\nhscan\r\n$1\r\nh\r\n$1\r\n0\r\n$5\r\nCOUNT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = HScan::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "syntax error");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_command() -> Result<()> {
+        let backend = Backend::default();
+        let pairs = (0..100)
+            .map(|i| (format!("field{}", i), bulk(&format!("value{}", i))))
+            .collect();
+        backend.hmset("h".to_string(), pairs)?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = HScan {
+                key: "h".to_string(),
+                cursor,
+                pattern: Some("field*".to_string()),
+                count: 9,
+                novalues: false,
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("HSCAN must reply an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(items)) = (&reply[0], &reply[1])
+            else {
+                panic!("HSCAN must reply a cursor and an array");
+            };
+            for pair in items.chunks(2) {
+                let (RespFrame::BulkString(field), RespFrame::BulkString(value)) =
+                    (&pair[0], &pair[1])
+                else {
+                    panic!("fields and values must be bulk strings");
+                };
+                assert_eq!(field[5..], value[5..]);
+                assert!(seen.insert(field.0.clone()), "field returned twice");
+            }
+            // fields added during the iteration don't break it
+            backend.hset("h".to_string(), format!("new{}", cursor), bulk("v"))?;
+            cursor = String::from_utf8_lossy(next).parse()?;
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 100);
+
+        let cmd = HScan {
+            key: "nosuchkey".to_string(),
+            cursor: 0,
+            pattern: None,
+            count: 10,
+            novalues: true,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![bulk("0"), RespArray(vec![]).into()]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*8\r\n$7\r\nhexpire\r\n$1\r\nh\r\n$2\r\n10\r\n$2\r\nnx\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HExpire = frame.try_into()?;
+        assert_eq!(result.key, "h");
+        assert_eq!(result.seconds, 10);
+        assert!(result.condition.nx);
+        assert_eq!(result.fields, ["a", "b"]);
+
+        let cases = [
+            (
+                "*5\r\n$7\r\nhexpire\r\n$1\r\nh\r\n$2\r\n10\r\n$1\r\n1\r\n$1\r\na\r\n",
+                "Mandatory argument FIELDS is missing or not at the right position",
+            ),
+            (
+                "*6\r\n$7\r\nhexpire\r\n$1\r\nh\r\n$2\r\n10\r\n$6\r\nfields\r\n$1\r\n0\r\n$1\r\na\r\n",
+                "Parameter `numFields` should be greater than 0",
+            ),
+            (
+                "*6\r\n$7\r\nhexpire\r\n$1\r\nh\r\n$2\r\n10\r\n$6\r\nfields\r\n$1\r\n2\r\n$1\r\na\r\n",
+                "The `numfields` parameter must match the number of arguments",
+            ),
+            (
+                "*6\r\n$7\r\nhexpire\r\n$1\r\nh\r\n$2\r\n-1\r\n$6\r\nfields\r\n$1\r\n1\r\n$1\r\na\r\n",
+                "invalid expire time, must be >= 0",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = HExpire::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hgetex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*7\r\n$6\r\nhgetex\r\n$1\r\nh\r\n$2\r\nPX\r\n$3\r\n100\r\n$6\r\nFIELDS\r\n$1\r\n1\r\n$1\r\na\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HGetEx = frame.try_into()?;
+        assert_eq!(result.key, "h");
+        assert_eq!(result.expiry, Some(SetExpiry::Px(100)));
+        assert!(!result.persist);
+        assert_eq!(result.fields, ["a"]);
+
+        let mut buf = BytesMut::from(
+            "*6\r\n$6\r\nhgetex\r\n$1\r\nh\r\n$7\r\npersist\r\n$6\r\nfields\r\n$1\r\n1\r\n$1\r\na\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HGetEx = frame.try_into()?;
+        assert!(result.persist);
+        assert_eq!(result.fields, ["a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_expiration_commands() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let backend = Backend::with_clock(clock.clone());
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let integers = |values: &[i64]| -> RespFrame {
+            RespArray(values.iter().map(|v| RespFrame::Integer(*v)).collect()).into()
+        };
+        backend.hmset(
+            "h".to_string(),
+            vec![
+                ("a".to_string(), bulk("1")),
+                ("b".to_string(), bulk("2")),
+                ("c".to_string(), bulk("3")),
+            ],
+        )?;
+
+        let cmd = HExpire {
+            key: "h".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::default(),
+            fields: fields(&["a", "b", "nosuchfield"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1, 1, -2]));
+        let cmd = HExpire {
+            key: "h".to_string(),
+            seconds: 10,
+            condition: ExpireCondition {
+                gt: true,
+                ..Default::default()
+            },
+            fields: fields(&["c"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[0]));
+
+        clock.advance(2_400);
+        let cmd = HTtl {
+            key: "h".to_string(),
+            fields: fields(&["a", "c", "nosuchfield"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[8, -1, -2]));
+        let cmd = HPersist {
+            key: "h".to_string(),
+            fields: fields(&["b", "c"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1, -1]));
+        // incrementing keeps the deadline
+        backend.hincr_by("h".to_string(), "a".to_string(), 1)?;
+
+        clock.advance(7_600);
+        assert_eq!(backend.hget("h", "a")?, None);
+        assert_eq!(backend.hlen("h")?, 2);
+        let expected: HashMap<_, _> = [("b".to_string(), bulk("2")), ("c".to_string(), bulk("3"))]
+            .into_iter()
+            .collect();
+        assert_eq!(backend.hgetall("h")?, Some(expected));
+
+        let cmd = HGetEx {
+            key: "h".to_string(),
+            expiry: Some(SetExpiry::Ex(5)),
+            persist: false,
+            fields: fields(&["b", "nosuchfield"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![bulk("2"), RespFrame::Null(RespNull)]).into()
+        );
+        let cmd = HTtl {
+            key: "h".to_string(),
+            fields: fields(&["b"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[5]));
+
+        // a deadline in the past deletes the field, and the hash with its last field
+        let cmd = HPExpire {
+            key: "h".to_string(),
+            milliseconds: 0,
+            condition: ExpireCondition::default(),
+            fields: fields(&["c"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[2]));
+        clock.advance(5_000);
+        assert_eq!(backend.hlen("h")?, 0);
+        assert_eq!(backend.key_type("h"), None);
+        let cmd = HTtl {
+            key: "h".to_string(),
+            fields: fields(&["b"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[-2]));
+
+        // HSET clears the deadline of the field
+        backend.hset("h".to_string(), "a".to_string(), bulk("1"))?;
+        let cmd = HExpire {
+            key: "h".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::default(),
+            fields: fields(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1]));
+        backend.hset("h".to_string(), "a".to_string(), bulk("2"))?;
+        clock.advance(20_000);
+        assert_eq!(backend.hget("h", "a")?, Some(bulk("2")));
+        Ok(())
+    }
 }
@@ -0,0 +1,460 @@
+use crate::{
+    backend::Backend,
+    resp::{BulkString, RespArray, RespFrame, RespNull, SimpleError, SimpleString},
+};
+
+use super::{
+    extract_args, extract_cursor, extract_scan_count, extract_string, extract_strings,
+    CommandError, CommandExecutor, Del, Exists, Keys, RandomKey, Rename, RenameNx, Scan, Type,
+    Unlink, RESP_OK, SCAN_DEFAULT_COUNT,
+};
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let name = backend.key_type(&self.key).unwrap_or("none");
+        RespFrame::SimpleSting(SimpleString(name.to_string()))
+    }
+}
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.del(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // values are dropped in place, there is no background freeing to defer to
+        RespFrame::Integer(backend.del(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.exists(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Keys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        keys_reply(backend.keys(&self.pattern))
+    }
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(
+            self.cursor,
+            self.count,
+            self.pattern.as_deref(),
+            self.type_name.as_deref(),
+        );
+        RespArray(vec![
+            BulkString(cursor.to_string().into_bytes()).into(),
+            keys_reply(keys),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.new_key, false) {
+            Some(_) => RESP_OK.clone(),
+            None => no_such_key(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.new_key, true) {
+            Some(renamed) => RespFrame::Integer(renamed as i64),
+            None => no_such_key(),
+        }
+    }
+}
+
+impl CommandExecutor for RandomKey {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.random_key() {
+            Some(key) => BulkString(key.into_bytes()).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+fn keys_reply(keys: Vec<String>) -> RespFrame {
+    RespArray(
+        keys.into_iter()
+            .map(|key| BulkString(key.into_bytes()).into())
+            .collect(),
+    )
+    .into()
+}
+
+fn no_such_key() -> RespFrame {
+    RespFrame::Error(SimpleError("ERR no such key".to_string()))
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Type {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unlink {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Exists {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Keys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Keys {
+            pattern: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut scan = Scan {
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: SCAN_DEFAULT_COUNT,
+            type_name: None,
+        };
+
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            let Some(arg) = args.next() else {
+                return Err(syntax_error());
+            };
+            match option.as_str() {
+                "match" => scan.pattern = Some(extract_string(Some(arg))?),
+                "count" => scan.count = extract_scan_count(Some(arg))?,
+                "type" => scan.type_name = Some(extract_string(Some(arg))?),
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(scan)
+    }
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Rename {
+            key: extract_string(args.next())?,
+            new_key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(RenameNx {
+            key: extract_string(args.next())?,
+            new_key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RandomKey {
+    type Error = CommandError;
+    fn try_from(_value: RespArray) -> Result<Self, Self::Error> {
+        Ok(RandomKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use crate::{
+        backend::{ExpireCondition, MockClock},
+        cmd::{Get, HGet, HSet, Set, SetOptions},
+        resp::RespDecode,
+        test_util::bulk,
+    };
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn wrongtype() -> RespFrame {
+        RespFrame::Error(SimpleError(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+        ))
+    }
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_type_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$4\r\ntype\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Type = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_and_wrongtype() -> Result<()> {
+        let backend = Backend::default();
+        let type_of = |key: &str| {
+            Type {
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(type_of("k"), SimpleString("none".into()).into());
+
+        let cmd = HSet {
+            key: "k".to_string(),
+            pairs: vec![(
+                "f".to_string(),
+                RespFrame::BulkString(BulkString("v".into())),
+            )],
+        };
+        cmd.execute(&backend);
+        assert_eq!(type_of("k"), SimpleString("hash".into()).into());
+
+        let cmd = Get {
+            key: "k".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), wrongtype());
+
+        let cmd = Set {
+            key: "k".to_string(),
+            value: RespFrame::BulkString(BulkString("v".into())),
+            options: SetOptions {
+                get: true,
+                ..Default::default()
+            },
+        };
+        assert_eq!(cmd.execute(&backend), wrongtype());
+        assert_eq!(type_of("k"), SimpleString("hash".into()).into());
+
+        // SET overwrites a key of any type
+        let cmd = Set {
+            key: "k".to_string(),
+            value: RespFrame::BulkString(BulkString("v".into())),
+            options: Default::default(),
+        };
+        cmd.execute(&backend);
+        assert_eq!(type_of("k"), SimpleString("string".into()).into());
+
+        let cmd = HGet {
+            key: "k".to_string(),
+            field: "f".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), wrongtype());
+        let cmd = HSet {
+            key: "k".to_string(),
+            pairs: vec![(
+                "f".to_string(),
+                RespFrame::BulkString(BulkString("v".into())),
+            )],
+        };
+        assert_eq!(cmd.execute(&backend), wrongtype());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*8\r\n$4\r\nscan\r\n$2\r\n42\r\n$5\r\nMATCH\r\n$2\r\nk*\r\n$5\r\ncount\r\n$3\r\n100\r\n$4\r\nTYPE\r\n$4\r\nhash\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Scan = frame.try_into()?;
+        assert_eq!(result.cursor, 42);
+        assert_eq!(result.pattern.as_deref(), Some("k*"));
+        assert_eq!(result.count, 100);
+        assert_eq!(result.type_name.as_deref(), Some("hash"));
+
+        let mut buf = BytesMut::from("*2\r\n$4\r\nscan\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Scan::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "invalid cursor");
+
+        let mut buf = BytesMut::from("*4\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\ncount\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Scan::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "syntax error");
+        Ok(())
+    }
+
+    #[test]
+    fn test_del_exists_keys() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        backend.set("a".to_string(), bulk("1"));
+        backend.set("b".to_string(), bulk("2"));
+        backend.set("expiring".to_string(), bulk("3"));
+        backend.hset("h".to_string(), "f".to_string(), bulk("v"))?;
+        backend.expire("expiring", 1_500, ExpireCondition::default());
+
+        let cmd = Exists {
+            keys: keys(&["a", "a", "h", "nosuchkey"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = Keys {
+            pattern: "*".to_string(),
+        };
+        let mut all = key_list(cmd.execute(&backend));
+        all.sort();
+        assert_eq!(all, keys(&["a", "b", "expiring", "h"]));
+
+        clock.advance(500);
+        let cmd = Keys {
+            pattern: "[ae]*".to_string(),
+        };
+        assert_eq!(key_list(cmd.execute(&backend)), keys(&["a"]));
+
+        // the expired key is not counted as deleted
+        let cmd = Del {
+            keys: keys(&["a", "expiring", "nosuchkey"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Unlink {
+            keys: keys(&["b", "h"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.dbsize(), 0);
+        assert_eq!(RandomKey.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_command() {
+        let backend = Backend::default();
+        for i in 0..50 {
+            backend.set(format!("key{}", i), bulk("v"));
+        }
+        for i in 0..10 {
+            backend
+                .hset(format!("hash{}", i), "f".to_string(), bulk("v"))
+                .unwrap();
+        }
+
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = Scan {
+                cursor,
+                pattern: Some("*[0-9]".to_string()),
+                count: 7,
+                type_name: Some("HASH".to_string()),
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("SCAN must reply an array");
+            };
+            let RespFrame::BulkString(next) = &reply[0] else {
+                panic!("the cursor must be a bulk string");
+            };
+            for key in key_list(reply[1].clone()) {
+                assert!(key.starts_with("hash"));
+                assert!(seen.insert(key), "key returned twice");
+            }
+            // keys added during the iteration don't break it
+            backend.set(format!("new{}", cursor), bulk("v"));
+            cursor = String::from_utf8_lossy(next).parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 10);
+    }
+
+    #[test]
+    fn test_rename_command() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        backend.set("a".to_string(), bulk("1"));
+        backend.set("b".to_string(), bulk("2"));
+        backend.expire("a", 5_000, ExpireCondition::default());
+
+        let cmd = RenameNx {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get("a"), Ok(None));
+        assert_eq!(backend.get("b"), Ok(Some(bulk("1"))));
+        // the deadline moves with the value
+        assert_eq!(backend.expire_at("b"), Some(Some(5_000)));
+
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "c".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(RandomKey.execute(&backend), bulk("c"));
+
+        let cmd = Rename {
+            key: "nosuchkey".to_string(),
+            new_key: "c".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError("ERR no such key".to_string()))
+        );
+    }
+
+    fn key_list(frame: RespFrame) -> Vec<String> {
+        let RespFrame::Array(keys) = frame else {
+            panic!("expected an array of keys");
+        };
+        keys.0
+            .into_iter()
+            .map(|key| match key {
+                RespFrame::BulkString(key) => String::from_utf8(key.0).unwrap(),
+                _ => panic!("keys must be bulk strings"),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,1186 @@
+use std::time::Duration;
+
+use crate::{
+    backend::{Backend, BackendError, ListEnd, ServeFn},
+    resp::{BulkString, RespArray, RespFrame, RespNull},
+};
+
+use super::{
+    blocking::{null_array, serve_now, BlockingCommand},
+    extract_args, extract_i64, extract_string, extract_strings, extract_timeout, integer_reply,
+    BLMPop, BLMove, BLPop, BRPop, CommandError, CommandExecutor, LIndex, LInsert, LLen, LMPop,
+    LMove, LPop, LPos, LPush, LRange, LRem, LSet, LTrim, RPop, RPopLPush, RPush, RESP_OK,
+};
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.push(self.key, ListEnd::Left, self.values))
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.push(self.key, ListEnd::Right, self.values))
+    }
+}
+
+impl CommandExecutor for LPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_generic(backend, &self.key, ListEnd::Left, self.count)
+    }
+}
+
+impl CommandExecutor for RPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_generic(backend, &self.key, ListEnd::Right, self.count)
+    }
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => RespArray(values).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.llen(&self.key))
+    }
+}
+
+impl CommandExecutor for LIndex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lindex(&self.key, self.index) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lset(&self.key, self.index, self.value) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LInsert {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.linsert(&self.key, self.before, &self.pivot, self.value) {
+            Ok(Some(len)) => RespFrame::Integer(len as i64),
+            Ok(None) => RespFrame::Integer(-1),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.lrem(&self.key, self.count, &self.element))
+    }
+}
+
+impl CommandExecutor for LTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        move_reply(backend.lmove(&self.source, &self.destination, self.from, self.to))
+    }
+}
+
+impl CommandExecutor for RPopLPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        move_reply(backend.lmove(
+            &self.source,
+            &self.destination,
+            ListEnd::Right,
+            ListEnd::Left,
+        ))
+    }
+}
+
+impl CommandExecutor for LMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.mpop(&self.keys, self.end, self.count) {
+            Ok(Some((key, values))) => key_reply(&key, RespArray(values).into()),
+            Ok(None) => null_array(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // without COUNT only the first match is wanted
+        let count = self.count.unwrap_or(1);
+        let indexes = match backend.lpos(&self.key, &self.element, self.rank, count, self.maxlen) {
+            Ok(indexes) => indexes,
+            Err(e) => return e.into(),
+        };
+        let mut indexes = indexes.into_iter().map(|i| RespFrame::Integer(i as i64));
+        match self.count {
+            Some(_) => RespArray(indexes.collect()).into(),
+            None => indexes.next().unwrap_or(RespFrame::Null(RespNull)),
+        }
+    }
+}
+
+fn move_reply(result: Result<Option<RespFrame>, BackendError>) -> RespFrame {
+    match result {
+        Ok(Some(value)) => value,
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+// outside of a blocking context, the blocking commands don't wait
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl CommandExecutor for BLMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl CommandExecutor for BLMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl BlockingCommand for BLPop {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        serve_pop(ListEnd::Left)
+    }
+
+    fn null_reply(&self) -> RespFrame {
+        null_array()
+    }
+}
+
+impl BlockingCommand for BRPop {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        serve_pop(ListEnd::Right)
+    }
+
+    fn null_reply(&self) -> RespFrame {
+        null_array()
+    }
+}
+
+impl BlockingCommand for BLMove {
+    fn keys(&self) -> Vec<String> {
+        vec![self.source.clone()]
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        let (destination, from, to) = (self.destination.clone(), self.from, self.to);
+        Box::new(move |backend, key| backend.lmove(key, &destination, from, to))
+    }
+}
+
+impl BlockingCommand for BLMPop {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        let (end, count) = (self.end, self.count);
+        Box::new(move |backend, key| {
+            Ok(backend
+                .pop(key, end, count)?
+                .map(|values| key_reply(key, RespArray(values).into())))
+        })
+    }
+
+    fn null_reply(&self) -> RespFrame {
+        null_array()
+    }
+}
+
+// pop one element, replied together with the key it was popped from
+fn serve_pop(end: ListEnd) -> ServeFn {
+    Box::new(move |backend, key| {
+        Ok(backend
+            .pop(key, end, 1)?
+            .and_then(|mut values| values.pop())
+            .map(|value| key_reply(key, value)))
+    })
+}
+
+fn key_reply(key: &str, value: RespFrame) -> RespFrame {
+    RespArray(vec![BulkString(key.into()).into(), value]).into()
+}
+
+// without a count the reply is the popped element, with one it's an array
+fn pop_generic(backend: &Backend, key: &str, end: ListEnd, count: Option<usize>) -> RespFrame {
+    match backend.pop(key, end, count.unwrap_or(1)) {
+        Ok(Some(values)) if count.is_some() => RespArray(values).into(),
+        Ok(Some(mut values)) => values.pop().unwrap_or(RespFrame::Null(RespNull)),
+        Ok(None) if count.is_some() => null_array(),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value)?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value)?;
+        Ok(RPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_args(value)?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_args(value)?;
+        Ok(RPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LRange {
+            key: extract_string(args.next())?,
+            start: extract_i64(args.next())?,
+            stop: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LIndex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LIndex {
+            key: extract_string(args.next())?,
+            index: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let index = extract_i64(args.next())?;
+        let Some(value) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(LSet { key, index, value })
+    }
+}
+
+impl TryFrom<RespArray> for LInsert {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // linsert key <BEFORE | AFTER> pivot element
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let before = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+            "before" => true,
+            "after" => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        let (Some(pivot), Some(value)) = (args.next(), args.next()) else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(LInsert {
+            key,
+            before,
+            pivot,
+            value,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = extract_i64(args.next())?;
+        let Some(element) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(LRem {
+            key,
+            count,
+            element,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LTrim {
+            key: extract_string(args.next())?,
+            start: extract_i64(args.next())?,
+            stop: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // lmove source destination <LEFT | RIGHT> <LEFT | RIGHT>
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RPopLPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(RPopLPush {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // lmpop numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+        let (keys, end, count) = parse_mpop_args(extract_args(value, 1)?)?;
+        Ok(LMPop { keys, end, count })
+    }
+}
+
+// same messages as redis
+const LPOS_ZERO_RANK: &str = "RANK can't be zero: use 1 to start from the first match, \
+    2 from the second ... or use negative to start from the end of the list";
+const LPOS_RANK_OUT_OF_RANGE: &str = "value is out of range, \
+    value must between -9223372036854775807 and 9223372036854775807";
+
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // lpos key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let Some(element) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        let mut lpos = LPos {
+            key,
+            element,
+            rank: 1,
+            count: None,
+            maxlen: 0,
+        };
+        let invalid = |message: &str| CommandError::InvalidArgument(message.to_string());
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            let known = matches!(option.as_str(), "rank" | "count" | "maxlen");
+            if !known || args.len() == 0 {
+                return Err(invalid("syntax error"));
+            }
+            let value = extract_i64(args.next())?;
+            match option.as_str() {
+                "rank" => {
+                    if value == 0 {
+                        return Err(invalid(LPOS_ZERO_RANK));
+                    }
+                    if value == i64::MIN {
+                        return Err(invalid(LPOS_RANK_OUT_OF_RANGE));
+                    }
+                    lpos.rank = value;
+                }
+                "count" => {
+                    let count = usize::try_from(value);
+                    lpos.count = Some(count.map_err(|_| invalid("COUNT can't be negative"))?);
+                }
+                _ => {
+                    let maxlen = usize::try_from(value);
+                    lpos.maxlen = maxlen.map_err(|_| invalid("MAXLEN can't be negative"))?;
+                }
+            }
+        }
+        Ok(lpos)
+    }
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_bpop_args(value)?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_bpop_args(value)?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BLMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // blmove source destination <LEFT | RIGHT> <LEFT | RIGHT> timeout
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(BLMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+            timeout: extract_timeout(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for BLMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // blmpop timeout numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = extract_timeout(args.next())?;
+        let (keys, end, count) = parse_mpop_args(args.collect())?;
+        Ok(BLMPop {
+            timeout,
+            keys,
+            end,
+            count,
+        })
+    }
+}
+
+// <cmd> key [key ...] timeout
+fn parse_bpop_args(value: RespArray) -> Result<(Vec<String>, Option<Duration>), CommandError> {
+    let mut args = extract_args(value, 1)?;
+    let timeout = extract_timeout(args.pop())?;
+    Ok((extract_strings(args)?, timeout))
+}
+
+// numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+fn parse_mpop_args(args: Vec<RespFrame>) -> Result<(Vec<String>, ListEnd, usize), CommandError> {
+    let mut args = args.into_iter();
+    let numkeys = extract_i64(args.next())?;
+    if numkeys <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    if numkeys as usize >= args.len() {
+        return Err(syntax_error());
+    }
+    let keys = extract_strings(args.by_ref().take(numkeys as usize).collect())?;
+    let end = extract_list_end(args.next())?;
+    let count = match args.next() {
+        None => 1,
+        Some(option) if extract_string(Some(option.clone()))?.eq_ignore_ascii_case("count") => {
+            let count = extract_i64(args.next())?;
+            if count <= 0 {
+                return Err(CommandError::InvalidArgument(
+                    "count should be greater than 0".to_string(),
+                ));
+            }
+            count as usize
+        }
+        Some(_) => return Err(syntax_error()),
+    };
+    if args.next().is_some() {
+        return Err(syntax_error());
+    }
+    Ok((keys, end, count))
+}
+
+fn extract_list_end(frame: Option<RespFrame>) -> Result<ListEnd, CommandError> {
+    match extract_string(frame)?.to_ascii_lowercase().as_str() {
+        "left" => Ok(ListEnd::Left),
+        "right" => Ok(ListEnd::Right),
+        _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+    }
+}
+
+// <cmd> key element [element ...]
+fn parse_push_args(value: RespArray) -> Result<(String, Vec<RespFrame>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    Ok((key, args.collect()))
+}
+
+// <cmd> key [count]
+fn parse_pop_args(value: RespArray) -> Result<(String, Option<usize>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(count) => Some(usize::try_from(extract_i64(Some(count))?).map_err(|_| {
+            CommandError::InvalidArgument("value is out of range, must be positive".to_string())
+        })?),
+        None => None,
+    };
+    Ok((key, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cmd::{Command, Session},
+        resp::{RespDecode, RespNullArray, SimpleError},
+        test_util::bulk,
+    };
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn list(values: &[&str]) -> RespFrame {
+        RespArray(values.iter().map(|v| bulk(v)).collect()).into()
+    }
+
+    fn lrange(backend: &Backend, key: &str) -> RespFrame {
+        LRange {
+            key: key.to_string(),
+            start: 0,
+            stop: -1,
+        }
+        .execute(backend)
+    }
+
+    #[test]
+    fn test_list_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LPush = frame.try_into()?;
+        assert_eq!(result.key, "list");
+        assert_eq!(result.values, [bulk("a"), bulk("b")]);
+
+        let mut buf = BytesMut::from("*3\r\n$4\r\nrpop\r\n$4\r\nlist\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: RPop = frame.try_into()?;
+        assert_eq!(result.count, Some(2));
+
+        let mut buf = BytesMut::from("*3\r\n$4\r\nrpop\r\n$4\r\nlist\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = RPop::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "value is out of range, must be positive");
+
+        let mut buf = BytesMut::from(
+            "*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$6\r\nBEFORE\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LInsert = frame.try_into()?;
+        assert!(result.before);
+        assert_eq!(result.pivot, bulk("a"));
+        assert_eq!(result.value, bulk("b"));
+
+        let mut buf = BytesMut::from(
+            "*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$6\r\nbehind\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let err = LInsert::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "syntax error");
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_pop_commands() -> Result<()> {
+        let backend = Backend::default();
+        let cmd = LPush {
+            key: "list".to_string(),
+            values: vec![bulk("b"), bulk("a")],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = RPush {
+            key: "list".to_string(),
+            values: vec![bulk("c"), bulk("d"), bulk("e")],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        assert_eq!(lrange(&backend, "list"), list(&["a", "b", "c", "d", "e"]));
+
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), bulk("a"));
+        let cmd = RPop {
+            key: "list".to_string(),
+            count: Some(2),
+        };
+        assert_eq!(cmd.execute(&backend), list(&["e", "d"]));
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: Some(10),
+        };
+        assert_eq!(cmd.execute(&backend), list(&["b", "c"]));
+
+        // the list is deleted with its last element
+        assert!(backend.db.is_empty());
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        let cmd = RPop {
+            key: "list".to_string(),
+            count: Some(1),
+        };
+        assert_eq!(cmd.execute(&backend), RespNullArray.into());
+
+        backend.set("string".to_string(), bulk("v"));
+        let cmd = LPush {
+            key: "string".to_string(),
+            values: vec![bulk("a")],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_index_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            ["a", "b", "c", "d", "e"].iter().map(|v| bulk(v)).collect(),
+        )?;
+
+        let cases = [
+            (0, -1, list(&["a", "b", "c", "d", "e"])),
+            (-3, 2, list(&["c"])),
+            (-100, 1, list(&["a", "b"])),
+            (3, 100, list(&["d", "e"])),
+            (3, 1, list(&[])),
+            (5, 10, list(&[])),
+        ];
+        for (start, stop, expected) in cases {
+            let cmd = LRange {
+                key: "list".to_string(),
+                start,
+                stop,
+            };
+            assert_eq!(cmd.execute(&backend), expected, "{} {}", start, stop);
+        }
+
+        let cmd = LLen {
+            key: "list".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = LIndex {
+            key: "list".to_string(),
+            index: -2,
+        };
+        assert_eq!(cmd.execute(&backend), bulk("d"));
+        let cmd = LIndex {
+            key: "list".to_string(),
+            index: 5,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = LSet {
+            key: "list".to_string(),
+            index: -1,
+            value: bulk("z"),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let cmd = LSet {
+            key: "list".to_string(),
+            index: 5,
+            value: bulk("z"),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError("ERR index out of range".to_string()))
+        );
+        let cmd = LSet {
+            key: "nosuchkey".to_string(),
+            index: 0,
+            value: bulk("z"),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError("ERR no such key".to_string()))
+        );
+
+        let cmd = LTrim {
+            key: "list".to_string(),
+            start: 1,
+            stop: -2,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(lrange(&backend, "list"), list(&["b", "c", "d"]));
+        let cmd = LTrim {
+            key: "list".to_string(),
+            start: 2,
+            stop: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(backend.db.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_linsert_lrem_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            ["a", "x", "b", "x", "c", "x"]
+                .iter()
+                .map(|v| bulk(v))
+                .collect(),
+        )?;
+
+        let cmd = LInsert {
+            key: "list".to_string(),
+            before: true,
+            pivot: bulk("b"),
+            value: bulk("y"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(7));
+        let cmd = LInsert {
+            key: "list".to_string(),
+            before: false,
+            pivot: bulk("c"),
+            value: bulk("y"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(8));
+        let cmd = LInsert {
+            key: "list".to_string(),
+            before: false,
+            pivot: bulk("nosuchpivot"),
+            value: bulk("y"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+        let cmd = LInsert {
+            key: "nosuchkey".to_string(),
+            before: false,
+            pivot: bulk("a"),
+            value: bulk("y"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            lrange(&backend, "list"),
+            list(&["a", "x", "y", "b", "x", "c", "y", "x"])
+        );
+
+        let cmd = LRem {
+            key: "list".to_string(),
+            count: -2,
+            element: bulk("x"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            lrange(&backend, "list"),
+            list(&["a", "x", "y", "b", "c", "y"])
+        );
+        let cmd = LRem {
+            key: "list".to_string(),
+            count: 1,
+            element: bulk("y"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(lrange(&backend, "list"), list(&["a", "x", "b", "c", "y"]));
+        let cmd = LRem {
+            key: "list".to_string(),
+            count: 0,
+            element: bulk("x"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(lrange(&backend, "list"), list(&["a", "b", "c", "y"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$5\r\nblpop\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n1.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: BLPop = frame.try_into()?;
+        assert_eq!(result.keys, ["a", "b"]);
+        assert_eq!(result.timeout, Some(Duration::from_millis(1_500)));
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nbrpop\r\n$1\r\na\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: BRPop = frame.try_into()?;
+        assert_eq!(result.timeout, None);
+
+        let mut buf = BytesMut::from(
+            "*8\r\n$6\r\nblmpop\r\n$1\r\n0\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nRIGHT\r\n$5\r\nCOUNT\r\n$1\r\n3\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: BLMPop = frame.try_into()?;
+        assert_eq!(result.keys, ["a", "b"]);
+        assert_eq!(result.end, ListEnd::Right);
+        assert_eq!(result.count, 3);
+
+        let cases = [
+            (
+                "*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$2\r\n-1\r\n",
+                "timeout is negative",
+            ),
+            (
+                "*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$3\r\nabc\r\n",
+                "timeout is not a float or out of range",
+            ),
+            (
+                "*5\r\n$6\r\nblmpop\r\n$1\r\n0\r\n$1\r\n0\r\n$1\r\na\r\n$4\r\nleft\r\n",
+                "numkeys should be greater than 0",
+            ),
+            (
+                "*5\r\n$6\r\nblmpop\r\n$1\r\n0\r\n$1\r\n2\r\n$1\r\na\r\n$4\r\nleft\r\n",
+                "syntax error",
+            ),
+            (
+                "*7\r\n$6\r\nblmpop\r\n$1\r\n0\r\n$1\r\n1\r\n$1\r\na\r\n$4\r\nleft\r\n$5\r\ncount\r\n$1\r\n0\r\n",
+                "count should be greater than 0",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = Command::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.push("list".to_string(), ListEnd::Right, vec![bulk("a")])?;
+        let cmd = BRPop {
+            keys: vec!["nosuchkey".to_string(), "list".to_string()],
+            timeout: None,
+        };
+        assert_eq!(
+            Command::from(cmd).run(&backend, &mut Session::new()).await,
+            RespArray(vec![bulk("list"), bulk("a")]).into()
+        );
+
+        let cmd = BLPop {
+            keys: vec!["list".to_string()],
+            timeout: Some(Duration::from_millis(10)),
+        };
+        assert_eq!(
+            Command::from(cmd).run(&backend, &mut Session::new()).await,
+            RespNullArray.into()
+        );
+
+        let blocked = tokio::spawn({
+            let backend = backend.clone();
+            let cmd = BLMPop {
+                timeout: None,
+                keys: vec!["list".to_string()],
+                end: ListEnd::Left,
+                count: 2,
+            };
+            async move { Command::from(cmd).run(&backend, &mut Session::new()).await }
+        });
+        // give the command time to block before pushing
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            vec![bulk("b"), bulk("c"), bulk("d")],
+        )?;
+        assert_eq!(
+            blocked.await?,
+            RespArray(vec![bulk("list"), list(&["b", "c"])]).into()
+        );
+        assert_eq!(lrange(&backend, "list"), list(&["d"]));
+
+        // renaming a list onto the key wakes the client up too
+        let blocked = tokio::spawn({
+            let backend = backend.clone();
+            let cmd = BLPop {
+                keys: vec!["q".to_string()],
+                timeout: Some(Duration::from_secs(2)),
+            };
+            async move { Command::from(cmd).run(&backend, &mut Session::new()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        backend.push("tmp".to_string(), ListEnd::Right, vec![bulk("x")])?;
+        backend.rename("tmp", "q".to_string(), false);
+        assert_eq!(blocked.await?, RespArray(vec![bulk("q"), bulk("x")]).into());
+
+        // outside of a blocking context the command doesn't wait
+        let cmd = BLMove {
+            source: "list".to_string(),
+            destination: "other".to_string(),
+            from: ListEnd::Left,
+            to: ListEnd::Left,
+            timeout: None,
+        };
+        assert_eq!(cmd.execute(&backend), bulk("d"));
+        let cmd = BLMove {
+            source: "list".to_string(),
+            destination: "other".to_string(),
+            from: ListEnd::Left,
+            to: ListEnd::Left,
+            timeout: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        assert_eq!(lrange(&backend, "other"), list(&["d"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*9\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$2\r\n-2\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n$6\r\nMAXLEN\r\n$2\r\n10\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LPos = frame.try_into()?;
+        assert_eq!(result.element, bulk("a"));
+        assert_eq!(result.rank, -2);
+        assert_eq!(result.count, Some(0));
+        assert_eq!(result.maxlen, 10);
+
+        let cases = [
+            (
+                "*5\r\n$4\r\nlpos\r\n$1\r\nl\r\n$1\r\na\r\n$4\r\nrank\r\n$1\r\n0\r\n",
+                LPOS_ZERO_RANK,
+            ),
+            (
+                "*5\r\n$4\r\nlpos\r\n$1\r\nl\r\n$1\r\na\r\n$5\r\ncount\r\n$2\r\n-1\r\n",
+                "COUNT can't be negative",
+            ),
+            (
+                "*5\r\n$4\r\nlpos\r\n$1\r\nl\r\n$1\r\na\r\n$6\r\nmaxlen\r\n$2\r\n-1\r\n",
+                "MAXLEN can't be negative",
+            ),
+            (
+                "*5\r\n$4\r\nlpos\r\n$1\r\nl\r\n$1\r\na\r\n$5\r\nlimit\r\n$1\r\nx\r\n",
+                "syntax error",
+            ),
+            (
+                "*4\r\n$4\r\nlpos\r\n$1\r\nl\r\n$1\r\na\r\n$4\r\nrank\r\n",
+                "syntax error",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = LPos::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.push(
+            "pending".to_string(),
+            ListEnd::Right,
+            vec![bulk("a"), bulk("b"), bulk("c")],
+        )?;
+
+        let cmd = LMove {
+            source: "pending".to_string(),
+            destination: "processing".to_string(),
+            from: ListEnd::Left,
+            to: ListEnd::Right,
+        };
+        assert_eq!(cmd.execute(&backend), bulk("a"));
+        let cmd = RPopLPush {
+            source: "pending".to_string(),
+            destination: "processing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), bulk("c"));
+        assert_eq!(lrange(&backend, "pending"), list(&["b"]));
+        assert_eq!(lrange(&backend, "processing"), list(&["c", "a"]));
+
+        // rotating a list of one element keeps it
+        let cmd = RPopLPush {
+            source: "pending".to_string(),
+            destination: "pending".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), bulk("b"));
+        assert_eq!(lrange(&backend, "pending"), list(&["b"]));
+
+        backend.set("string".to_string(), bulk("v"));
+        let cmd = RPopLPush {
+            source: "pending".to_string(),
+            destination: "string".to_string(),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+        assert_eq!(lrange(&backend, "pending"), list(&["b"]));
+        let cmd = RPopLPush {
+            source: "nosuchkey".to_string(),
+            destination: "pending".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop_lpos_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            ["a", "b", "a", "c", "a"].iter().map(|v| bulk(v)).collect(),
+        )?;
+
+        let lpos = |rank, count, maxlen| {
+            LPos {
+                key: "list".to_string(),
+                element: bulk("a"),
+                rank,
+                count,
+                maxlen,
+            }
+            .execute(&backend)
+        };
+        let integers = |values: &[i64]| -> RespFrame {
+            RespArray(values.iter().map(|v| RespFrame::Integer(*v)).collect()).into()
+        };
+        assert_eq!(lpos(1, None, 0), RespFrame::Integer(0));
+        assert_eq!(lpos(2, None, 0), RespFrame::Integer(2));
+        assert_eq!(lpos(-1, None, 0), RespFrame::Integer(4));
+        assert_eq!(lpos(4, None, 0), RespFrame::Null(RespNull));
+        assert_eq!(lpos(1, Some(0), 0), integers(&[0, 2, 4]));
+        assert_eq!(lpos(-2, Some(0), 0), integers(&[2, 0]));
+        assert_eq!(lpos(1, Some(2), 0), integers(&[0, 2]));
+        assert_eq!(lpos(1, Some(0), 3), integers(&[0, 2]));
+        assert_eq!(lpos(-1, Some(0), 2), integers(&[4]));
+
+        let cmd = LMPop {
+            keys: vec!["nosuchkey".to_string(), "list".to_string()],
+            end: ListEnd::Right,
+            count: 2,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![bulk("list"), list(&["a", "c"])]).into()
+        );
+        let cmd = LMPop {
+            keys: vec!["nosuchkey".to_string()],
+            end: ListEnd::Left,
+            count: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RespNullArray.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_is_atomic() -> Result<()> {
+        let backend = Backend::default();
+        let values: Vec<_> = (0..400).map(|i| bulk(&i.to_string())).collect();
+        backend.push("pending".to_string(), ListEnd::Right, values)?;
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    while let Ok(Some(_)) =
+                        backend.lmove("pending", "processing", ListEnd::Left, ListEnd::Right)
+                    {
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(backend.llen("pending")?, 0);
+        let mut moved: Vec<_> = backend
+            .lrange("processing", 0, -1)?
+            .iter()
+            .map(|value| match value {
+                RespFrame::BulkString(s) => String::from_utf8_lossy(s).parse::<i32>().unwrap(),
+                _ => panic!("expected a bulk string"),
+            })
+            .collect();
+        moved.sort_unstable();
+        assert_eq!(moved, (0..400).collect::<Vec<_>>());
+        Ok(())
+    }
+}
@@ -1,28 +1,269 @@
-use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::{
+    backend::{Backend, SetCondition, SetTtl},
+    resp::{encode::format_double, BulkString, RespArray, RespFrame, RespNull, SimpleError},
+};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, Get, Set, RESP_OK};
+use super::{
+    extract_args, extract_bytes, extract_f64, extract_i64, extract_pairs, extract_string,
+    extract_strings, integer_reply, Append, CommandError, CommandExecutor, Decr, DecrBy, Get,
+    GetDel, GetEx, GetRange, GetSet, Incr, IncrBy, IncrByFloat, MGet, MSet, MSetNx, PSetEx, Set,
+    SetEx, SetExpiry, SetNx, SetOptions, SetRange, Strlen, RESP_OK,
+};
 
 impl CommandExecutor for Get {
-    fn execute(self, backend: &crate::backend::Backend) -> RespFrame {
+    fn execute(self, backend: &Backend) -> RespFrame {
         match backend.get(&self.key) {
-            Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for Set {
-    fn execute(self, backend: &crate::backend::Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.options.expiry {
+            None => Some(SetTtl::Clear),
+            Some(expiry) => expiry.to_ttl(backend.now_ms()),
+        };
+        let Some(ttl) = ttl else {
+            return invalid_expire_time("set");
+        };
+
+        let (written, old) = match backend.set_with(
+            self.key,
+            self.value,
+            self.options.condition,
+            ttl,
+            self.options.get,
+        ) {
+            Ok(result) => result,
+            Err(e) => return e.into(),
+        };
+        if self.options.get {
+            old.unwrap_or(RespFrame::Null(RespNull))
+        } else if written {
+            RESP_OK.clone()
+        } else {
+            RespFrame::Null(RespNull)
+        }
+    }
+}
+
+impl CommandExecutor for GetSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.set_with(
+            self.key,
+            self.value,
+            SetCondition::Always,
+            SetTtl::Clear,
+            true,
+        ) {
+            Ok((_, old)) => old.unwrap_or(RespFrame::Null(RespNull)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.expiry {
+            _ if self.persist => Some(SetTtl::Clear),
+            None => Some(SetTtl::Keep),
+            Some(expiry) => expiry.to_ttl(backend.now_ms()),
+        };
+        let Some(ttl) = ttl else {
+            return invalid_expire_time("getex");
+        };
+        match backend.getex(&self.key, ttl) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.set_with(
+            self.key,
+            self.value,
+            SetCondition::IfNotExists,
+            SetTtl::Clear,
+            false,
+        ) {
+            Ok((written, _)) => RespFrame::Integer(written as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        setex(
+            backend,
+            self.key,
+            SetExpiry::Ex(self.seconds),
+            self.value,
+            "setex",
+        )
+    }
+}
+
+impl CommandExecutor for PSetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        setex(
+            backend,
+            self.key,
+            SetExpiry::Px(self.milliseconds),
+            self.value,
+            "psetex",
+        )
+    }
+}
+
+fn setex(
+    backend: &Backend,
+    key: String,
+    expiry: SetExpiry,
+    value: RespFrame,
+    name: &str,
+) -> RespFrame {
+    let Some(ttl) = expiry.to_ttl(backend.now_ms()) else {
+        return invalid_expire_time(name);
+    };
+    match backend.set_with(key, value, SetCondition::Always, ttl, false) {
+        Ok(_) => RESP_OK.clone(),
+        Err(e) => e.into(),
+    }
+}
+
+impl SetExpiry {
+    // None if the deadline overflows
+    pub(super) fn to_ttl(&self, now: i64) -> Option<SetTtl> {
+        match *self {
+            SetExpiry::KeepTtl => Some(SetTtl::Keep),
+            SetExpiry::Ex(seconds) => seconds
+                .checked_mul(1000)
+                .and_then(|ms| ms.checked_add(now))
+                .map(SetTtl::At),
+            SetExpiry::Px(ms) => ms.checked_add(now).map(SetTtl::At),
+            SetExpiry::ExAt(timestamp) => timestamp.checked_mul(1000).map(SetTtl::At),
+            SetExpiry::PxAt(timestamp_ms) => Some(SetTtl::At(timestamp_ms)),
+        }
+    }
+}
+
+pub(super) fn invalid_expire_time(name: &str) -> RespFrame {
+    RespFrame::Error(SimpleError(format!(
+        "ERR invalid expire time in '{}' command",
+        name
+    )))
+}
+
+impl CommandExecutor for MGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray(
+            backend
+                .mget(&self.keys)
+                .into_iter()
+                .map(|value| value.unwrap_or(RespFrame::Null(RespNull)))
+                .collect(),
+        )
+        .into()
+    }
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.mset(self.pairs);
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for MSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.msetnx(self.pairs) as i64)
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.incr_by(self.key, 1))
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.incr_by(self.key, -1))
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.incr_by(self.key, self.increment))
+    }
+}
+
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // -i64::MIN doesn't fit in an i64
+        let Some(increment) = self.decrement.checked_neg() else {
+            return RespFrame::Error(SimpleError("ERR decrement would overflow".to_string()));
+        };
+        integer_reply(backend.incr_by(self.key, increment))
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(self.key, self.increment) {
+            Ok(value) => BulkString(format_double(value).into_bytes()).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.append(self.key, &self.value))
+    }
+}
+
+impl CommandExecutor for Strlen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.strlen(&self.key))
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => BulkString(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.setrange(self.key, self.offset as usize, &self.value))
+    }
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["get"], 1)?;
-
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
@@ -36,26 +277,313 @@ impl TryFrom<RespArray> for Get {
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        // set key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+        //   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => (String::from_utf8(key.0)?, value),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid key or value".to_string(),
+                ))
+            }
+        };
+
+        let mut options = SetOptions::default();
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            match option.as_str() {
+                "nx" if options.condition != SetCondition::IfExists => {
+                    options.condition = SetCondition::IfNotExists
+                }
+                "xx" if options.condition != SetCondition::IfNotExists => {
+                    options.condition = SetCondition::IfExists
+                }
+                "get" => options.get = true,
+                "keepttl" if options.expiry.is_none() => options.expiry = Some(SetExpiry::KeepTtl),
+                "ex" | "px" | "exat" | "pxat" if options.expiry.is_none() => {
+                    options.expiry = Some(parse_expiry(&option, args.next(), "set")?);
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+
+        Ok(Set {
+            key,
+            value,
+            options,
+        })
+    }
+}
 
+// the time argument of EX / PX / EXAT / PXAT
+pub(super) fn parse_expiry(
+    option: &str,
+    time: Option<RespFrame>,
+    name: &str,
+) -> Result<SetExpiry, CommandError> {
+    let Some(time) = time else {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    };
+    let time = extract_i64(Some(time))?;
+    if time <= 0 {
+        return Err(CommandError::InvalidArgument(format!(
+            "invalid expire time in '{}' command",
+            name
+        )));
+    }
+    Ok(match option {
+        "ex" => SetExpiry::Ex(time),
+        "px" => SetExpiry::Px(time),
+        "exat" => SetExpiry::ExAt(time),
+        _ => SetExpiry::PxAt(time),
+    })
+}
+
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
-                value: value,
-            }),
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key or value".to_string(),
-            )),
+        let key = extract_string(args.next())?;
+        let Some(value) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(GetSet { key, value })
+    }
+}
+
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetDel {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // getex key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+        //   PXAT unix-time-milliseconds | PERSIST]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut getex = GetEx {
+            key: extract_string(args.next())?,
+            expiry: None,
+            persist: false,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            let unset = getex.expiry.is_none() && !getex.persist;
+            match option.as_str() {
+                "persist" if unset => getex.persist = true,
+                "ex" | "px" | "exat" | "pxat" if unset => {
+                    getex.expiry = Some(parse_expiry(&option, args.next(), "getex")?);
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(getex)
+    }
+}
+
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let Some(value) = args.next() else {
+            return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+        };
+        Ok(SetNx { key, value })
+    }
+}
+
+impl TryFrom<RespArray> for SetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, value) = extract_setex_args(value, "setex")?;
+        Ok(SetEx {
+            key,
+            seconds,
+            value,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, value) = extract_setex_args(value, "psetex")?;
+        Ok(PSetEx {
+            key,
+            milliseconds,
+            value,
+        })
+    }
+}
+
+// key time value
+fn extract_setex_args(
+    value: RespArray,
+    name: &str,
+) -> Result<(String, i64, RespFrame), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let time = extract_i64(args.next())?;
+    if time <= 0 {
+        return Err(CommandError::InvalidArgument(format!(
+            "invalid expire time in '{}' command",
+            name
+        )));
+    }
+    let Some(value) = args.next() else {
+        return Err(CommandError::InvalidArgument("Invalid value".to_string()));
+    };
+    Ok((key, time, value))
+}
+
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MGet {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSet {
+            pairs: extract_pairs(extract_args(value, 1)?, "mset")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for MSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSetNx {
+            pairs: extract_pairs(extract_args(value, 1)?, "msetnx")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Incr {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Decr {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(IncrBy {
+            key: extract_string(args.next())?,
+            increment: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(DecrBy {
+            key: extract_string(args.next())?,
+            decrement: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(IncrByFloat {
+            key: extract_string(args.next())?,
+            increment: extract_f64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Append {
+            key: extract_string(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Strlen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Strlen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetRange {
+            key: extract_string(args.next())?,
+            start: extract_i64(args.next())?,
+            end: extract_i64(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let offset = extract_i64(args.next())?;
+        if offset < 0 {
+            return Err(CommandError::InvalidArgument(
+                "offset is out of range".to_string(),
+            ));
         }
+        Ok(SetRange {
+            key,
+            offset,
+            value: extract_bytes(args.next())?,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::{
-        backend::Backend,
-        resp::{BulkString, RespDecode},
+        backend::{ExpireCondition, MockClock},
+        resp::RespDecode,
+        test_util::bulk,
     };
     use anyhow::Result;
     use bytes::BytesMut;
@@ -91,6 +619,7 @@ mod tests {
         let cmd = Set {
             key: "hello".to_string(),
             value: RespFrame::BulkString(BulkString("world".into())),
+            options: SetOptions::default(),
         };
 
         let result = cmd.execute(&backend);
@@ -104,4 +633,592 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_options_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*6\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nNX\r\n$2\r\nPX\r\n$5\r\n30000\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(
+            result.options,
+            SetOptions {
+                condition: SetCondition::IfNotExists,
+                expiry: Some(SetExpiry::Px(30000)),
+                get: false,
+            }
+        );
+
+        let mut buf = BytesMut::from(
+            "*5\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nget\r\n$7\r\nkeepttl\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert!(result.options.get);
+        assert_eq!(result.options.expiry, Some(SetExpiry::KeepTtl));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_conflicting_options() -> Result<()> {
+        let cases = [
+            "*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nnx\r\n$2\r\nxx\r\n",
+            "*6\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n$1\r\n1\r\n$7\r\nkeepttl\r\n",
+            "*7\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n$1\r\n1\r\n$2\r\npx\r\n$1\r\n1\r\n",
+            "*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n",
+            "*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n$1\r\n0\r\n",
+            "*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n$1\r\na\r\n",
+            "*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$3\r\nfoo\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            let result: Result<Set, _> = frame.try_into();
+            assert!(result.is_err(), "{:?} should be rejected", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_nx_xx_get_command() -> Result<()> {
+        let backend = Backend::default();
+        let set = |value: &str, options| Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(BulkString(value.into())),
+            options,
+        };
+
+        let cmd = set(
+            "v1",
+            SetOptions {
+                condition: SetCondition::IfExists,
+                ..Default::default()
+            },
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        assert_eq!(backend.get("hello"), Ok(None));
+
+        let cmd = set(
+            "v1",
+            SetOptions {
+                condition: SetCondition::IfNotExists,
+                ..Default::default()
+            },
+        );
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = set(
+            "v2",
+            SetOptions {
+                condition: SetCondition::IfNotExists,
+                get: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(BulkString("v1".into()))
+        );
+        assert_eq!(
+            backend.get("hello"),
+            Ok(Some(RespFrame::BulkString(BulkString("v1".into()))))
+        );
+
+        let cmd = set(
+            "v3",
+            SetOptions {
+                condition: SetCondition::IfExists,
+                get: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(BulkString("v1".into()))
+        );
+        assert_eq!(
+            backend.get("hello"),
+            Ok(Some(RespFrame::BulkString(BulkString("v3".into()))))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_expiry_command() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let backend = Backend::with_clock(clock.clone());
+        let set = |options| Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(BulkString("world".into())),
+            options,
+        };
+
+        let cmd = set(SetOptions {
+            expiry: Some(SetExpiry::Ex(10)),
+            ..Default::default()
+        });
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.expire_at("hello"), Some(Some(1_010_000)));
+
+        let cmd = set(SetOptions {
+            expiry: Some(SetExpiry::KeepTtl),
+            ..Default::default()
+        });
+        cmd.execute(&backend);
+        assert_eq!(backend.expire_at("hello"), Some(Some(1_010_000)));
+
+        let cmd = set(SetOptions {
+            expiry: Some(SetExpiry::PxAt(1_000_500)),
+            ..Default::default()
+        });
+        cmd.execute(&backend);
+        assert_eq!(backend.expire_at("hello"), Some(Some(1_000_500)));
+
+        let cmd = set(SetOptions::default());
+        cmd.execute(&backend);
+        assert_eq!(backend.expire_at("hello"), Some(None));
+
+        let cmd = set(SetOptions {
+            expiry: Some(SetExpiry::ExAt(1_001)),
+            ..Default::default()
+        });
+        cmd.execute(&backend);
+        clock.advance(1_000);
+        assert_eq!(backend.get("hello"), Ok(None));
+
+        let cmd = set(SetOptions {
+            expiry: Some(SetExpiry::Ex(i64::MAX)),
+            ..Default::default()
+        });
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError(
+                "ERR invalid expire time in 'set' command".to_string()
+            ))
+        );
+        Ok(())
+    }
+
+    fn error(msg: &str) -> RespFrame {
+        RespFrame::Error(SimpleError(msg.to_string()))
+    }
+
+    #[test]
+    fn test_getex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$5\r\nGETEX\r\n$1\r\nk\r\n$2\r\nPX\r\n$3\r\n100\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: GetEx = frame.try_into()?;
+        assert_eq!(result.key, "k");
+        assert_eq!(result.expiry, Some(SetExpiry::Px(100)));
+        assert!(!result.persist);
+
+        let cases = [
+            "*4\r\n$5\r\ngetex\r\n$1\r\nk\r\n$7\r\npersist\r\n$7\r\npersist\r\n",
+            "*5\r\n$5\r\ngetex\r\n$1\r\nk\r\n$2\r\nex\r\n$1\r\n1\r\n$7\r\npersist\r\n",
+            "*3\r\n$5\r\ngetex\r\n$1\r\nk\r\n$2\r\nex\r\n",
+            "*3\r\n$5\r\ngetex\r\n$1\r\nk\r\n$7\r\nkeepttl\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            let err = GetEx::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), "syntax error", "{:?}", case);
+        }
+
+        let mut buf = BytesMut::from("*4\r\n$5\r\ngetex\r\n$1\r\nk\r\n$2\r\nex\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = GetEx::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "invalid expire time in 'getex' command");
+        Ok(())
+    }
+
+    #[test]
+    fn test_getset_getdel_command() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let getset = |value: &str| {
+            GetSet {
+                key: "k".to_string(),
+                value: bulk(value),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getset("v1"), RespFrame::Null(RespNull));
+        backend.expire("k", 5_000, ExpireCondition::default());
+        assert_eq!(getset("v2"), bulk("v1"));
+        // like SET, GETSET clears the deadline
+        assert_eq!(backend.expire_at("k"), Some(None));
+
+        let getdel = |key: &str| {
+            GetDel {
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getdel("k"), bulk("v2"));
+        assert_eq!(getdel("k"), RespFrame::Null(RespNull));
+
+        backend.hset("hash".to_string(), "f".to_string(), bulk("v"))?;
+        let wrongtype = error("WRONGTYPE Operation against a key holding the wrong kind of value");
+        assert_eq!(getdel("hash"), wrongtype);
+        let cmd = GetSet {
+            key: "hash".to_string(),
+            value: bulk("v"),
+        };
+        assert_eq!(cmd.execute(&backend), wrongtype);
+        assert_eq!(backend.key_type("hash"), Some("hash"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_command() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        backend.set("k".to_string(), bulk("v"));
+        let getex = |expiry, persist| {
+            GetEx {
+                key: "k".to_string(),
+                expiry,
+                persist,
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(getex(Some(SetExpiry::Ex(10)), false), bulk("v"));
+        assert_eq!(backend.expire_at("k"), Some(Some(11_000)));
+        assert_eq!(getex(None, false), bulk("v"));
+        assert_eq!(backend.expire_at("k"), Some(Some(11_000)));
+        assert_eq!(getex(Some(SetExpiry::PxAt(20_000)), false), bulk("v"));
+        assert_eq!(backend.expire_at("k"), Some(Some(20_000)));
+        assert_eq!(getex(None, true), bulk("v"));
+        assert_eq!(backend.expire_at("k"), Some(None));
+
+        // a deadline in the past deletes the key after returning it
+        assert_eq!(getex(Some(SetExpiry::ExAt(1)), false), bulk("v"));
+        assert_eq!(backend.get("k"), Ok(None));
+        assert_eq!(getex(None, false), RespFrame::Null(RespNull));
+
+        backend.set("k".to_string(), bulk("v"));
+        assert_eq!(
+            getex(Some(SetExpiry::Ex(i64::MAX)), false),
+            error("ERR invalid expire time in 'getex' command")
+        );
+    }
+
+    #[test]
+    fn test_setnx_setex_command() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let setnx = |value: &str| {
+            SetNx {
+                key: "k".to_string(),
+                value: bulk(value),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(setnx("v1"), RespFrame::Integer(1));
+        assert_eq!(setnx("v2"), RespFrame::Integer(0));
+        assert_eq!(backend.get("k"), Ok(Some(bulk("v1"))));
+
+        let cmd = SetEx {
+            key: "k".to_string(),
+            seconds: 10,
+            value: bulk("v3"),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get("k"), Ok(Some(bulk("v3"))));
+        assert_eq!(backend.expire_at("k"), Some(Some(11_000)));
+
+        let cmd = PSetEx {
+            key: "k".to_string(),
+            milliseconds: 500,
+            value: bulk("v4"),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.expire_at("k"), Some(Some(1_500)));
+        clock.advance(500);
+        assert_eq!(backend.get("k"), Ok(None));
+
+        let mut buf = BytesMut::from("*4\r\n$5\r\nsetex\r\n$1\r\nk\r\n$2\r\n-1\r\n$1\r\nv\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = SetEx::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "invalid expire time in 'setex' command");
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_from_resp_array() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*5\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: MSet = frame.try_into()?;
+        assert_eq!(
+            result.pairs,
+            vec![("a".to_string(), bulk("1")), ("b".to_string(), bulk("2"))]
+        );
+
+        let mut buf = BytesMut::from("*4\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = MSet::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'mset' command"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_mget_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.hset("hash".to_string(), "f".to_string(), bulk("v"))?;
+        let cmd = MSet {
+            pairs: vec![("a".to_string(), bulk("1")), ("b".to_string(), bulk("2"))],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = MGet {
+            keys: vec!["a".into(), "hash".into(), "nosuchkey".into(), "b".into()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![
+                bulk("1"),
+                RespFrame::Null(RespNull),
+                RespFrame::Null(RespNull),
+                bulk("2"),
+            ])
+            .into()
+        );
+
+        // nothing is written if any key exists
+        let cmd = MSetNx {
+            pairs: vec![("c".to_string(), bulk("3")), ("a".to_string(), bulk("x"))],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.get("c"), Ok(None));
+        assert_eq!(backend.get("a"), Ok(Some(bulk("1"))));
+
+        let cmd = MSetNx {
+            pairs: vec![("c".to_string(), bulk("3")), ("d".to_string(), bulk("4"))],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("d"), Ok(Some(bulk("4"))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_is_atomic() {
+        let backend = Backend::default();
+        let writer = {
+            let backend = backend.clone();
+            std::thread::spawn(move || {
+                for i in 0..1000 {
+                    let value = bulk(&i.to_string());
+                    let pairs = (0..10).map(|k| (format!("k{}", k), value.clone()));
+                    backend.mset(pairs.collect());
+                }
+            })
+        };
+        let keys: Vec<String> = (0..10).map(|k| format!("k{}", k)).collect();
+        while !writer.is_finished() {
+            let values = backend.mget(&keys);
+            assert!(values.windows(2).all(|w| w[0] == w[1]), "{:?}", values);
+        }
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_incr_decr_command() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let key = || "counter".to_string();
+
+        assert_eq!(Incr { key: key() }.execute(&backend), RespFrame::Integer(1));
+        let cmd = IncrBy {
+            key: key(),
+            increment: 41,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(42));
+        let cmd = DecrBy {
+            key: key(),
+            decrement: 50,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-8));
+        assert_eq!(
+            Decr { key: key() }.execute(&backend),
+            RespFrame::Integer(-9)
+        );
+        assert_eq!(backend.get("counter"), Ok(Some(bulk("-9"))));
+
+        // the deadline is kept
+        backend.expire("counter", 5_000, ExpireCondition::default());
+        Incr { key: key() }.execute(&backend);
+        assert_eq!(backend.expire_at("counter"), Some(Some(5_000)));
+
+        backend.set(key(), bulk("9223372036854775806"));
+        assert_eq!(
+            Incr { key: key() }.execute(&backend),
+            RespFrame::Integer(i64::MAX)
+        );
+        assert_eq!(
+            Incr { key: key() }.execute(&backend),
+            error("ERR increment or decrement would overflow")
+        );
+        let cmd = DecrBy {
+            key: key(),
+            decrement: i64::MIN,
+        };
+        assert_eq!(cmd.execute(&backend), error("ERR decrement would overflow"));
+
+        for value in ["abc", "1.5", " 1", "+1", "01", ""] {
+            backend.set(key(), bulk(value));
+            assert_eq!(
+                Incr { key: key() }.execute(&backend),
+                error("ERR value is not an integer or out of range"),
+                "{:?} is not an integer",
+                value
+            );
+        }
+
+        backend.hset("hash".to_string(), "f".to_string(), bulk("1"))?;
+        let cmd = Incr {
+            key: "hash".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            error("WRONGTYPE Operation against a key holding the wrong kind of value")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_command() -> Result<()> {
+        let backend = Backend::default();
+        let incr = |increment| IncrByFloat {
+            key: "f".to_string(),
+            increment,
+        };
+        assert_eq!(incr(10.5).execute(&backend), bulk("10.5"));
+        assert_eq!(incr(0.1).execute(&backend), bulk("10.6"));
+        assert_eq!(incr(-5.0).execute(&backend), bulk("5.6"));
+        assert_eq!(backend.get("f"), Ok(Some(bulk("5.6"))));
+        // same formatting as RESP3 doubles
+        assert_eq!(
+            incr(1e10).execute(&backend),
+            bulk(&format_double(5.6 + 1e10))
+        );
+
+        assert_eq!(
+            incr(f64::INFINITY).execute(&backend),
+            error("ERR increment would produce NaN or Infinity")
+        );
+        backend.set("f".to_string(), bulk("abc"));
+        assert_eq!(
+            incr(1.0).execute(&backend),
+            error("ERR value is not a valid float")
+        );
+
+        let mut buf = BytesMut::from("*3\r\n$11\r\nincrbyfloat\r\n$1\r\nf\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = IncrByFloat::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "value is not a valid float");
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_strlen_command() -> Result<()> {
+        let backend = Backend::default();
+        let strlen = || {
+            Strlen {
+                key: "s".to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(strlen(), RespFrame::Integer(0));
+        let cmd = Append {
+            key: "s".to_string(),
+            value: "hello".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = Append {
+            key: "s".to_string(),
+            value: " world".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(strlen(), RespFrame::Integer(11));
+        assert_eq!(backend.get("s"), Ok(Some(bulk("hello world"))));
+
+        // values are binary-safe
+        let binary = |args: Vec<&[u8]>| {
+            RespArray(
+                args.into_iter()
+                    .map(|a| BulkString(a.to_vec()).into())
+                    .collect(),
+            )
+        };
+        let cmd = Append::try_from(binary(vec![b"append", b"b", b"\xff\xfe"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = SetRange::try_from(binary(vec![b"setrange", b"b", b"1", b"\x00\xfd"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            backend.get("b"),
+            Ok(Some(BulkString(b"\xff\x00\xfd".to_vec()).into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange_setrange_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("s".to_string(), bulk("This is a string"));
+        let getrange = |start, end| {
+            GetRange {
+                key: "s".to_string(),
+                start,
+                end,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getrange(0, 3), bulk("This"));
+        assert_eq!(getrange(-3, -1), bulk("ing"));
+        assert_eq!(getrange(0, -1), bulk("This is a string"));
+        assert_eq!(getrange(10, 100), bulk("string"));
+        assert_eq!(getrange(5, 3), bulk(""));
+        assert_eq!(getrange(-1, -5), bulk(""));
+
+        let setrange = |key: &str, offset, value: &str| {
+            SetRange {
+                key: key.to_string(),
+                offset,
+                value: value.into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(setrange("s", 10, "STRING"), RespFrame::Integer(16));
+        assert_eq!(backend.get("s"), Ok(Some(bulk("This is a STRING"))));
+
+        // padded with zero bytes
+        assert_eq!(setrange("padded", 3, "abc"), RespFrame::Integer(6));
+        assert_eq!(
+            backend.get("padded"),
+            Ok(Some(RespFrame::BulkString(BulkString(
+                b"\0\0\0abc".to_vec()
+            ))))
+        );
+        // an empty value doesn't create the key
+        assert_eq!(setrange("empty", 3, ""), RespFrame::Integer(0));
+        assert_eq!(backend.get("empty"), Ok(None));
+        assert_eq!(
+            setrange("s", 512 * 1024 * 1024, "x"),
+            error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")
+        );
+
+        let mut buf = BytesMut::from("*4\r\n$8\r\nsetrange\r\n$1\r\ns\r\n$2\r\n-1\r\n$1\r\nx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = SetRange::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "offset is out of range");
+        Ok(())
+    }
 }
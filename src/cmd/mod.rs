@@ -1,14 +1,39 @@
+mod blocking;
+mod connection;
+mod expire;
 mod hmap;
+mod keyspace;
+mod list;
 mod map;
+mod server;
+mod set;
+mod stream;
+mod table;
+mod zset;
+
+use std::time::Duration;
 
 use crate::{
-    backend::Backend,
-    resp::{RespArray, RespError, RespFrame, SimpleString},
+    backend::{
+        Backend, BackendError, ExpireCondition, ListEnd, SetCondition, StreamFields, StreamId,
+        StreamTrim, XAddId, XAddOptions, ZAddOptions, ZCombineOptions, ZRangeSpec, ZSetEnd,
+    },
+    resp::{RespArray, RespError, RespFrame, SimpleError, SimpleString},
 };
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
 use thiserror::Error;
 
+pub use connection::Session;
+
+// same default as redis
+const SCAN_DEFAULT_COUNT: usize = 10;
+
+// the most members a negative count of SRANDMEMBER or HRANDFIELD may ask for. They
+// may repeat, so the reply isn't bounded by the size of the key and is built while
+// its shard is locked.
+const RANDOM_COUNT_MAX: i64 = 1_000_000;
+
 lazy_static! {
     static ref RESP_OK: RespFrame = RespFrame::SimpleSting(SimpleString("OK".into()));
 }
@@ -18,18 +43,25 @@ pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
 }
 
+// the Display output is sent to the client after an "ERR " prefix
 #[derive(Error, Debug)]
 pub enum CommandError {
-    #[error("Invalid command: {0}")]
+    #[error("{0}")]
     InvalidCommand(String),
 
-    #[error("Invalid argument: {0}")]
+    #[error("{0}")]
     InvalidArgument(String),
 
-    #[error("{0}")]
+    #[error("wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+
+    #[error("unknown command '{name}', with args beginning with: {args}")]
+    UnknownCommand { name: String, args: String },
+
+    #[error("Protocol error: {0}")]
     RespError(#[from] RespError),
 
-    #[error("Utf8 error: {0}")]
+    #[error("invalid UTF-8 argument: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
@@ -38,10 +70,135 @@ pub enum CommandError {
 pub enum Command {
     Get(Get),
     Set(Set),
+    GetSet(GetSet),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    SetNx(SetNx),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    Append(Append),
+    Strlen(Strlen),
+    GetRange(GetRange),
+    SetRange(SetRange),
     HGet(HGet),
     HSet(HSet),
     HGetAll(HGetAll),
-    UnknownCmd(UnknownCmd),
+    HDel(HDel),
+    HExists(HExists),
+    HLen(HLen),
+    HKeys(HKeys),
+    HVals(HVals),
+    HMGet(HMGet),
+    HMSet(HMSet),
+    HSetNx(HSetNx),
+    HStrlen(HStrlen),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HRandField(HRandField),
+    HScan(HScan),
+    HExpire(HExpire),
+    HPExpire(HPExpire),
+    HTtl(HTtl),
+    HPersist(HPersist),
+    HGetEx(HGetEx),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LRange(LRange),
+    LLen(LLen),
+    LIndex(LIndex),
+    LSet(LSet),
+    LInsert(LInsert),
+    LRem(LRem),
+    LTrim(LTrim),
+    LMove(LMove),
+    RPopLPush(RPopLPush),
+    LMPop(LMPop),
+    LPos(LPos),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    BLMove(BLMove),
+    BLMPop(BLMPop),
+    SAdd(SAdd),
+    SRem(SRem),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    SMIsMember(SMIsMember),
+    SCard(SCard),
+    SPop(SPop),
+    SRandMember(SRandMember),
+    SMove(SMove),
+    SInter(SInter),
+    SUnion(SUnion),
+    SDiff(SDiff),
+    SInterStore(SInterStore),
+    SUnionStore(SUnionStore),
+    SDiffStore(SDiffStore),
+    SInterCard(SInterCard),
+    ZAdd(ZAdd),
+    ZRem(ZRem),
+    ZScore(ZScore),
+    ZIncrBy(ZIncrBy),
+    ZCard(ZCard),
+    ZRank(ZRank),
+    ZRevRank(ZRevRank),
+    ZRange(ZRange),
+    ZRangeStore(ZRangeStore),
+    ZRevRange(ZRevRange),
+    ZRangeByScore(ZRangeByScore),
+    ZRevRangeByScore(ZRevRangeByScore),
+    ZRangeByLex(ZRangeByLex),
+    ZRevRangeByLex(ZRevRangeByLex),
+    ZCount(ZCount),
+    ZLexCount(ZLexCount),
+    ZUnion(ZUnion),
+    ZInter(ZInter),
+    ZDiff(ZDiff),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    ZDiffStore(ZDiffStore),
+    ZPopMin(ZPopMin),
+    ZPopMax(ZPopMax),
+    ZMPop(ZMPop),
+    BZPopMin(BZPopMin),
+    BZPopMax(BZPopMax),
+    BZMPop(BZMPop),
+    XAdd(XAdd),
+    XRange(XRange),
+    XRevRange(XRevRange),
+    XLen(XLen),
+    XTrim(XTrim),
+    XDel(XDel),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    Persist(Persist),
+    Type(Type),
+    Del(Del),
+    Unlink(Unlink),
+    Exists(Exists),
+    Keys(Keys),
+    Scan(Scan),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    RandomKey(RandomKey),
+    DbSize(DbSize),
+    FlushDb(FlushDb),
+    FlushAll(FlushAll),
+    CommandCmd(CommandCmd),
+    Hello(Hello),
 }
 
 #[derive(Debug)]
@@ -53,6 +210,129 @@ pub struct Get {
 pub struct Set {
     key: String,
     value: RespFrame,
+    options: SetOptions,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SetOptions {
+    condition: SetCondition,
+    expiry: Option<SetExpiry>,
+    get: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SetExpiry {
+    Ex(i64),
+    Px(i64),
+    ExAt(i64),
+    PxAt(i64),
+    KeepTtl,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    key: String,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: String,
+    expiry: Option<SetExpiry>,
+    persist: bool,
+}
+
+#[derive(Debug)]
+pub struct SetNx {
+    key: String,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct SetEx {
+    key: String,
+    seconds: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct PSetEx {
+    key: String,
+    milliseconds: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    key: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    key: String,
+    decrement: i64,
+}
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: String,
+    increment: f64,
+}
+
+#[derive(Debug)]
+pub struct Append {
+    key: String,
+    value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Strlen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: String,
+    offset: i64,
+    value: Vec<u8>,
 }
 
 #[derive(Debug)]
@@ -63,105 +343,915 @@ pub struct HGet {
 
 #[derive(Debug)]
 pub struct HSet {
+    key: String,
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct HGetAll {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    key: String,
+    field: String,
+}
+
+#[derive(Debug)]
+pub struct HLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HKeys {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HMGet {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HMSet {
+    key: String,
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct HSetNx {
     key: String,
     field: String,
     value: RespFrame,
 }
 
 #[derive(Debug)]
-pub struct HGetAll {
+pub struct HStrlen {
+    key: String,
+    field: String,
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
     key: String,
+    field: String,
+    increment: i64,
 }
 
 #[derive(Debug)]
-pub struct UnknownCmd;
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    increment: f64,
+}
 
-impl TryFrom<RespFrame> for Command {
-    type Error = CommandError;
-    fn try_from(value: RespFrame) -> Result<Self, Self::Error> {
-        match value {
-            RespFrame::Array(array) => array.try_into(),
-            _ => Err(CommandError::InvalidCommand(format!(
-                "COmmand must be an Array"
-            ))),
-        }
-    }
+#[derive(Debug)]
+pub struct HScan {
+    key: String,
+    cursor: u64,
+    pattern: Option<String>,
+    count: usize,
+    novalues: bool,
 }
 
-impl TryFrom<RespArray> for Command {
-    type Error = CommandError;
-    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        match value.first() {
-            Some(RespFrame::BulkString(cmd)) => match cmd.as_slice() {
-                b"get" => Ok(Command::Get(value.try_into()?)),
-                b"set" => Ok(Command::Set(value.try_into()?)),
-                b"hget" => Ok(Command::HGet(value.try_into()?)),
-                b"hset" => Ok(Command::HSet(value.try_into()?)),
-                b"hgetall" => Ok(Command::HGetAll(value.try_into()?)),
-                _ => Ok(Command::UnknownCmd(UnknownCmd)),
-            },
-            _ => Err(CommandError::InvalidCommand(format!(
-                "Command mut have a BulkString as the first argument"
-            ))),
-        }
-    }
+#[derive(Debug)]
+pub struct HExpire {
+    key: String,
+    seconds: i64,
+    condition: ExpireCondition,
+    fields: Vec<String>,
 }
 
-// enum_dispatch 的功能
+#[derive(Debug)]
+pub struct HPExpire {
+    key: String,
+    milliseconds: i64,
+    condition: ExpireCondition,
+    fields: Vec<String>,
+}
 
-// impl CommandExecutor for Command {
-//     fn execute(self, backend: &Backend) -> RespFrame {
-//         match self {
-//             Self::Get(get) => get.execute(&backend),
-//             Self::Set(set) => set.execute(&backend),
-//             Self::HGet(hget) => hget.execute(&backend),
-//             Self::HSet(hset) => hset.execute(&backend),
-//             Self::HGetAll(hgetall) => hgetall.execute(&backend),
-//         }
-//     }
-// }
+#[derive(Debug)]
+pub struct HTtl {
+    key: String,
+    fields: Vec<String>,
+}
 
-fn validate_command(
-    value: &RespArray,
-    names: &[&'static str],
-    n_args: usize,
-) -> Result<(), CommandError> {
-    if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have exactly {} argument",
-            names.join(" "),
-            n_args
-        )));
-    }
-    for (i, name) in names.iter().enumerate() {
-        match value[i] {
-            RespFrame::BulkString(ref cmd) => {
-                if cmd.to_ascii_lowercase() != name.as_bytes() {
-                    return Err(CommandError::InvalidCommand(format!(
-                        "Invalid command: expected {}, got {}",
-                        name,
-                        String::from_utf8_lossy(cmd)
-                    )));
-                }
-            }
-            _ => {
-                return Err(CommandError::InvalidCommand(format!(
-                    "{} command must have a BulkString as the first argument",
-                    name
-                )));
-            }
-        }
-    }
+#[derive(Debug)]
+pub struct HPersist {
+    key: String,
+    fields: Vec<String>,
+}
 
-    Ok(())
+#[derive(Debug)]
+pub struct HGetEx {
+    key: String,
+    expiry: Option<SetExpiry>,
+    persist: bool,
+    fields: Vec<String>,
 }
 
-fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
-    Ok(value.0.into_iter().skip(start).collect())
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct LIndex {
+    key: String,
+    index: i64,
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    key: String,
+    index: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: String,
+    before: bool,
+    pivot: RespFrame,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    element: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    source: String,
+    destination: String,
+    from: ListEnd,
+    to: ListEnd,
+}
+
+#[derive(Debug)]
+pub struct RPopLPush {
+    source: String,
+    destination: String,
+}
+
+#[derive(Debug)]
+pub struct LMPop {
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    key: String,
+    element: RespFrame,
+    rank: i64,
+    count: Option<usize>,
+    maxlen: usize,
+}
+
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMove {
+    source: String,
+    destination: String,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    timeout: Option<Duration>,
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct SAdd {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SRem {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SIsMember {
+    key: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct SMIsMember {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SCard {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SRandMember {
+    key: String,
+    count: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct SMove {
+    source: String,
+    destination: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct SInter {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnion {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiff {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterCard {
+    keys: Vec<String>,
+    limit: usize,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    key: String,
+    options: ZAddOptions,
+    incr: bool,
+    pairs: Vec<(f64, String)>,
+}
+
+#[derive(Debug)]
+pub struct ZRem {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZScore {
+    key: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: String,
+    increment: f64,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct ZCard {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct ZRank {
+    key: String,
+    member: String,
+    with_score: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRank {
+    key: String,
+    member: String,
+    with_score: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRange {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeStore {
+    destination: String,
+    key: String,
+    spec: ZRangeSpec,
+}
+
+#[derive(Debug)]
+pub struct ZRevRange {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByScore {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRangeByScore {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRangeByLex {
+    key: String,
+    spec: ZRangeSpec,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZCount {
+    key: String,
+    spec: ZRangeSpec,
+}
+
+#[derive(Debug)]
+pub struct ZLexCount {
+    key: String,
+    spec: ZRangeSpec,
+}
+
+#[derive(Debug)]
+pub struct ZUnion {
+    keys: Vec<String>,
+    options: ZCombineOptions,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZInter {
+    keys: Vec<String>,
+    options: ZCombineOptions,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZDiff {
+    keys: Vec<String>,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZUnionStore {
+    destination: String,
+    keys: Vec<String>,
+    options: ZCombineOptions,
+}
+
+#[derive(Debug)]
+pub struct ZInterStore {
+    destination: String,
+    keys: Vec<String>,
+    options: ZCombineOptions,
+}
+
+#[derive(Debug)]
+pub struct ZDiffStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZPopMin {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ZPopMax {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ZMPop {
+    keys: Vec<String>,
+    end: ZSetEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct BZPopMin {
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BZPopMax {
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BZMPop {
+    timeout: Option<Duration>,
+    keys: Vec<String>,
+    end: ZSetEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct XAdd {
+    key: String,
+    id: XAddId,
+    fields: StreamFields,
+    options: XAddOptions,
+}
+
+#[derive(Debug)]
+pub struct XRange {
+    key: String,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct XRevRange {
+    key: String,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct XLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct XTrim {
+    key: String,
+    trim: StreamTrim,
+}
+
+#[derive(Debug)]
+pub struct XDel {
+    key: String,
+    ids: Vec<StreamId>,
+}
+
+#[derive(Debug)]
+pub struct HRandField {
+    key: String,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    key: String,
+    milliseconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: String,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: String,
+    timestamp_ms: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Persist {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Type {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Keys {
+    pattern: String,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    pattern: Option<String>,
+    count: usize,
+    type_name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RandomKey;
+
+#[derive(Debug)]
+pub struct DbSize;
+
+#[derive(Debug)]
+pub struct FlushDb;
+
+#[derive(Debug)]
+pub struct FlushAll;
+
+#[derive(Debug)]
+pub struct CommandCmd {
+    subcommand: CommandSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandSubcommand {
+    List,
+    Count,
+    Info(Vec<String>),
+    Docs(Vec<String>),
+    GetKeys(RespArray),
+}
+
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<i64>,
+}
+
+impl From<CommandError> for RespFrame {
+    fn from(value: CommandError) -> Self {
+        RespFrame::Error(SimpleError(format!("ERR {}", value)))
+    }
+}
+
+impl From<BackendError> for RespFrame {
+    fn from(value: BackendError) -> Self {
+        RespFrame::Error(SimpleError(value.to_string()))
+    }
+}
+
+impl TryFrom<RespFrame> for Command {
+    type Error = CommandError;
+    fn try_from(value: RespFrame) -> Result<Self, Self::Error> {
+        match value {
+            RespFrame::Array(array) => array.try_into(),
+            _ => Err(CommandError::InvalidCommand(
+                "Command must be an Array".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Command {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match value.first() {
+            Some(RespFrame::BulkString(cmd)) => {
+                let name = String::from_utf8_lossy(cmd).to_ascii_lowercase();
+                match table::lookup(&name) {
+                    Some(spec) => {
+                        if !spec.check_arity(value.len()) {
+                            return Err(CommandError::WrongArity(spec.name.to_string()));
+                        }
+                        (spec.parse)(value)
+                    }
+                    None => Err(unknown_command(&value)),
+                }
+            }
+            _ => Err(CommandError::InvalidCommand(
+                "Command must have a BulkString as the first argument".to_string(),
+            )),
+        }
+    }
+}
+
+// same format as redis: the name and the args are each cut to 128 bytes
+fn unknown_command(value: &RespArray) -> CommandError {
+    let printable = |frame: &RespFrame| match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(s).into_owned(),
+        other => format!("{:?}", other),
+    };
+    let mut name = printable(&value[0]);
+    truncate(&mut name, 128);
+
+    let mut args = String::new();
+    for arg in value.iter().skip(1) {
+        if args.len() >= 128 {
+            break;
+        }
+        let mut arg = printable(arg);
+        truncate(&mut arg, 128 - args.len());
+        args.push_str(&format!("'{}' ", arg));
+    }
+    CommandError::UnknownCommand { name, args }
+}
+
+fn truncate(s: &mut String, max: usize) {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+}
+
+// the reply of a command returning a count or a length, or the error it failed with
+fn integer_reply<T: TryInto<i64>>(result: Result<T, BackendError>) -> RespFrame {
+    match result {
+        Ok(value) => RespFrame::Integer(value.try_into().unwrap_or(i64::MAX)),
+        Err(e) => e.into(),
+    }
+}
+
+// enum_dispatch 的功能
+
+// impl CommandExecutor for Command {
+//     fn execute(self, backend: &Backend) -> RespFrame {
+//         match self {
+//             Self::Get(get) => get.execute(&backend),
+//             Self::Set(set) => set.execute(&backend),
+//             Self::HGet(hget) => hget.execute(&backend),
+//             Self::HSet(hset) => hset.execute(&backend),
+//             Self::HGetAll(hgetall) => hgetall.execute(&backend),
+//         }
+//     }
+// }
+
+fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
+    Ok(value.0.into_iter().skip(start).collect())
+}
+
+fn extract_string(frame: Option<RespFrame>) -> Result<String, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(String::from_utf8(s.0)?),
+        _ => Err(CommandError::InvalidArgument(
+            "argument must be a BulkString".to_string(),
+        )),
+    }
+}
+
+// a binary-safe argument, like a string value
+fn extract_bytes(frame: Option<RespFrame>) -> Result<Vec<u8>, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(s.0),
+        _ => Err(CommandError::InvalidArgument(
+            "argument must be a BulkString".to_string(),
+        )),
+    }
+}
+
+fn extract_strings(args: Vec<RespFrame>) -> Result<Vec<String>, CommandError> {
+    args.into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect()
+}
+
+// name value [name value ...]
+fn extract_pairs(
+    args: Vec<RespFrame>,
+    command: &str,
+) -> Result<Vec<(String, RespFrame)>, CommandError> {
+    if !args.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity(command.to_string()));
+    }
+    let mut args = args.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(name), Some(value)) = (args.next(), args.next()) {
+        pairs.push((extract_string(Some(name))?, value));
+    }
+    Ok(pairs)
+}
+
+fn extract_cursor(frame: Option<RespFrame>) -> Result<u64, CommandError> {
+    extract_string(frame)?
+        .parse()
+        .map_err(|_| CommandError::InvalidArgument("invalid cursor".to_string()))
+}
+
+// the COUNT option of the SCAN family
+fn extract_scan_count(frame: Option<RespFrame>) -> Result<usize, CommandError> {
+    let count = extract_i64(frame)?;
+    if count < 1 {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok(count as usize)
+}
+
+fn extract_i64(frame: Option<RespFrame>) -> Result<i64, CommandError> {
+    match frame {
+        Some(RespFrame::Integer(i)) => Ok(i),
+        Some(RespFrame::BulkString(s)) => std::str::from_utf8(&s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| {
+                CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+            }),
+        _ => Err(CommandError::InvalidArgument(
+            "value is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+// the count of SRANDMEMBER and HRANDFIELD, see `RANDOM_COUNT_MAX`
+fn extract_random_count(frame: Option<RespFrame>) -> Result<i64, CommandError> {
+    let count = extract_i64(frame)?;
+    if count < -RANDOM_COUNT_MAX {
+        return Err(CommandError::InvalidArgument(
+            "value is out of range".to_string(),
+        ));
+    }
+    Ok(count)
+}
+
+// timeout of a blocking command in seconds, 0 blocks forever
+fn extract_timeout(frame: Option<RespFrame>) -> Result<Option<Duration>, CommandError> {
+    let timeout = extract_f64(frame).map_err(|_| {
+        CommandError::InvalidArgument("timeout is not a float or out of range".to_string())
+    })?;
+    if timeout < 0.0 {
+        return Err(CommandError::InvalidArgument(
+            "timeout is negative".to_string(),
+        ));
+    }
+    if timeout == 0.0 {
+        return Ok(None);
+    }
+    Duration::try_from_secs_f64(timeout)
+        .map(Some)
+        .map_err(|_| CommandError::InvalidArgument("timeout is out of range".to_string()))
 }
 
-impl CommandExecutor for UnknownCmd {
-    fn execute(self, backend: &Backend) -> RespFrame {
-        RESP_OK.clone()
+fn extract_f64(frame: Option<RespFrame>) -> Result<f64, CommandError> {
+    let invalid = || CommandError::InvalidArgument("value is not a valid float".to_string());
+    match frame {
+        Some(RespFrame::Integer(i)) => Ok(i as f64),
+        Some(RespFrame::Double(d)) if !d.is_nan() => Ok(d),
+        Some(RespFrame::BulkString(s)) => std::str::from_utf8(&s)
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|d| !d.is_nan())
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
     }
 }
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use crate::{
+    backend::Backend,
+    resp::{RespArray, RespFrame, RespMap, RespNull, SimpleError},
+};
+
+use super::{
+    extract_args, extract_string, table, CommandCmd, CommandError, CommandExecutor,
+    CommandSubcommand, DbSize, FlushAll, FlushDb, RESP_OK,
+};
+
+impl CommandExecutor for CommandCmd {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            CommandSubcommand::List => {
+                RespArray(table::all().iter().map(|spec| spec.info()).collect()).into()
+            }
+            CommandSubcommand::Count => RespFrame::Integer(table::all().len() as i64),
+            CommandSubcommand::Info(names) if names.is_empty() => {
+                RespArray(table::all().iter().map(|spec| spec.info()).collect()).into()
+            }
+            CommandSubcommand::Info(names) => RespArray(
+                names
+                    .iter()
+                    .map(|name| match table::lookup(&name.to_ascii_lowercase()) {
+                        Some(spec) => spec.info(),
+                        None => RespFrame::Null(RespNull),
+                    })
+                    .collect(),
+            )
+            .into(),
+            CommandSubcommand::Docs(names) => {
+                let specs = if names.is_empty() {
+                    table::all()
+                } else {
+                    names
+                        .iter()
+                        .filter_map(|name| table::lookup(&name.to_ascii_lowercase()))
+                        .collect()
+                };
+                let mut docs = RespMap(HashMap::new());
+                for spec in specs {
+                    docs.insert(spec.name.to_string(), spec.docs_reply());
+                }
+                docs.into()
+            }
+            CommandSubcommand::GetKeys(args) => getkeys(args),
+        }
+    }
+}
+
+fn getkeys(args: RespArray) -> RespFrame {
+    let error = |msg: &str| RespFrame::Error(SimpleError(format!("ERR {}", msg)));
+    let spec = match args.first() {
+        Some(RespFrame::BulkString(name)) => {
+            table::lookup(&String::from_utf8_lossy(name).to_ascii_lowercase())
+        }
+        _ => None,
+    };
+    let Some(spec) = spec else {
+        return error("Invalid command specified");
+    };
+    if !spec.check_arity(args.len()) {
+        return error("Invalid number of arguments specified for command");
+    }
+    let Some(positions) = spec.key_positions(&args) else {
+        return error("Invalid arguments specified for command");
+    };
+    if positions.is_empty() {
+        return error("The command has no key arguments");
+    }
+    RespArray(positions.into_iter().map(|i| args[i].clone()).collect()).into()
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for FlushDb {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush();
+        RESP_OK.clone()
+    }
+}
+
+// there is a single database, so FLUSHALL is the same as FLUSHDB
+impl CommandExecutor for FlushAll {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush();
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+    fn try_from(_value: RespArray) -> Result<Self, Self::Error> {
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for FlushDb {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        check_flush_mode(value)?;
+        Ok(FlushDb)
+    }
+}
+
+impl TryFrom<RespArray> for FlushAll {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        check_flush_mode(value)?;
+        Ok(FlushAll)
+    }
+}
+
+// ASYNC and SYNC are accepted, flushing is always synchronous
+fn check_flush_mode(value: RespArray) -> Result<(), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match args.next() {
+        None => Ok(()),
+        Some(mode) if args.len() == 0 => {
+            let mode = extract_string(Some(mode))?.to_ascii_lowercase();
+            match mode.as_str() {
+                "async" | "sync" => Ok(()),
+                _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Some(_) => Err(CommandError::InvalidArgument("syntax error".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for CommandCmd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let Some(subcommand) = args.next() else {
+            return Ok(CommandCmd {
+                subcommand: CommandSubcommand::List,
+            });
+        };
+        let subcommand = extract_string(Some(subcommand))?.to_ascii_lowercase();
+        let subcommand = match subcommand.as_str() {
+            "count" if args.len() == 0 => CommandSubcommand::Count,
+            "info" => CommandSubcommand::Info(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "docs" => CommandSubcommand::Docs(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "getkeys" if args.len() > 0 => CommandSubcommand::GetKeys(RespArray(args.collect())),
+            "count" | "getkeys" => {
+                return Err(CommandError::WrongArity(format!("command|{}", subcommand)))
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try COMMAND HELP.",
+                    subcommand
+                )))
+            }
+        };
+        Ok(CommandCmd { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cmd::Command,
+        resp::{BulkString, RespDecode},
+        test_util::command,
+    };
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<RespFrame> {
+        let cmd: Command = command(args).try_into()?;
+        Ok(cmd.execute(&Backend::default()))
+    }
+
+    #[test]
+    fn test_command_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$7\r\nCOMMAND\r\n$4\r\nINFO\r\n$3\r\nget\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: CommandCmd = frame.try_into()?;
+        assert_eq!(
+            result.subcommand,
+            CommandSubcommand::Info(vec!["get".to_string()])
+        );
+
+        let result = CommandCmd::try_from(command(&["command", "count", "x"]));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "wrong number of arguments for 'command|count' command"
+        );
+        let result = CommandCmd::try_from(command(&["command", "foo"]));
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_count_and_list() -> Result<()> {
+        let count = table::all().len() as i64;
+        assert_eq!(run(&["command", "count"])?, RespFrame::Integer(count));
+
+        let RespFrame::Array(list) = run(&["command"])? else {
+            panic!("COMMAND must reply an array");
+        };
+        assert_eq!(list.len() as i64, count);
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_info_and_docs() -> Result<()> {
+        let reply = run(&["command", "info", "GET", "nosuchcommand"])?;
+        assert_eq!(
+            reply,
+            RespArray(vec![
+                table::lookup("get").unwrap().info(),
+                RespFrame::Null(RespNull)
+            ])
+            .into()
+        );
+
+        let RespFrame::Map(docs) = run(&["command", "docs", "set", "nosuchcommand"])? else {
+            panic!("COMMAND DOCS must reply a map");
+        };
+        assert_eq!(docs.len(), 1);
+        let RespFrame::Map(set) = &docs["set"] else {
+            panic!("docs of a command must be a map");
+        };
+        assert_eq!(set["group"], BulkString("string".into()).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dbsize_and_flush() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("a".to_string(), BulkString("1".into()).into());
+        backend.set("b".to_string(), BulkString("2".into()).into());
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(2));
+
+        let cmd: Command = command(&["flushall", "ASYNC"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(0));
+
+        let result = Command::try_from(command(&["flushdb", "lazy"]));
+        assert_eq!(result.unwrap_err().to_string(), "syntax error");
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_getkeys() -> Result<()> {
+        let reply = run(&["command", "getkeys", "set", "hello", "world", "nx"])?;
+        assert_eq!(
+            reply,
+            RespArray(vec![BulkString("hello".into()).into()]).into()
+        );
+
+        let reply = run(&["command", "getkeys", "get"])?;
+        assert_eq!(
+            reply,
+            RespFrame::Error(SimpleError(
+                "ERR Invalid number of arguments specified for command".to_string()
+            ))
+        );
+
+        let reply = run(&["command", "getkeys", "command", "count"])?;
+        assert_eq!(
+            reply,
+            RespFrame::Error(SimpleError(
+                "ERR The command has no key arguments".to_string()
+            ))
+        );
+
+        let reply = run(&["command", "getkeys", "del", "a", "b"])?;
+        assert_eq!(
+            reply,
+            RespArray(vec![
+                BulkString("a".into()).into(),
+                BulkString("b".into()).into()
+            ])
+            .into()
+        );
+
+        let reply = run(&["command", "getkeys", "foo"])?;
+        assert_eq!(
+            reply,
+            RespFrame::Error(SimpleError("ERR Invalid command specified".to_string()))
+        );
+
+        // keys given by a numkeys argument
+        let cases: [(&[&str], &[&str]); 7] = [
+            (&["blmpop", "0", "2", "a", "b", "left"], &["a", "b"]),
+            (&["lmpop", "2", "a", "b", "left"], &["a", "b"]),
+            (&["sintercard", "2", "a", "b", "limit", "1"], &["a", "b"]),
+            (&["zunion", "2", "a", "b", "weights", "1", "2"], &["a", "b"]),
+            (&["zinterstore", "d", "2", "a", "b"], &["d", "a", "b"]),
+            (&["zmpop", "2", "a", "b", "min"], &["a", "b"]),
+            (
+                &["bzmpop", "0", "2", "a", "b", "max", "count", "2"],
+                &["a", "b"],
+            ),
+        ];
+        for (args, keys) in cases {
+            let reply = run(&[&["command", "getkeys"], args].concat())?;
+            let keys = keys
+                .iter()
+                .map(|key| BulkString(key.as_bytes().to_vec()).into());
+            assert_eq!(reply, RespArray(keys.collect()).into(), "{:?}", args);
+        }
+        let reply = run(&["command", "getkeys", "blmpop", "0", "5", "a", "b", "left"])?;
+        assert_eq!(
+            reply,
+            RespFrame::Error(SimpleError(
+                "ERR Invalid arguments specified for command".to_string()
+            ))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,713 @@
+use crate::{
+    backend::{Backend, SetOp},
+    resp::{BulkString, RespArray, RespFrame, RespNull, RespSet},
+};
+
+use super::{
+    extract_args, extract_i64, extract_random_count, extract_string, extract_strings,
+    integer_reply, CommandError, CommandExecutor, SAdd, SCard, SDiff, SDiffStore, SInter,
+    SInterCard, SInterStore, SIsMember, SMIsMember, SMembers, SMove, SPop, SRandMember, SRem,
+    SUnion, SUnionStore,
+};
+
+impl CommandExecutor for SAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sadd(self.key, self.members))
+    }
+}
+
+impl CommandExecutor for SRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.srem(&self.key, &self.members))
+    }
+}
+
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => set_reply(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SIsMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sismember(&self.key, &self.member).map(i64::from))
+    }
+}
+
+impl CommandExecutor for SMIsMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smismember(&self.key, &self.members) {
+            Ok(found) => RespArray(
+                found
+                    .into_iter()
+                    .map(|found| RespFrame::Integer(found.into()))
+                    .collect(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.scard(&self.key))
+    }
+}
+
+// without a count the reply is the popped member, with one it's a set
+impl CommandExecutor for SPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.spop(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) if self.count.is_some() => set_reply(members),
+            Ok(members) => match members.into_iter().next() {
+                Some(member) => BulkString(member.into_bytes()).into(),
+                None => RespFrame::Null(RespNull),
+            },
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// with a count the reply is an array, since a negative count may repeat members
+impl CommandExecutor for SRandMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) if self.count.is_some() => RespArray(
+                members
+                    .into_iter()
+                    .map(|member| BulkString(member.into_bytes()).into())
+                    .collect(),
+            )
+            .into(),
+            Ok(members) => match members.into_iter().next() {
+                Some(member) => BulkString(member.into_bytes()).into(),
+                None => RespFrame::Null(RespNull),
+            },
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(
+            backend
+                .smove(&self.source, &self.destination, &self.member)
+                .map(i64::from),
+        )
+    }
+}
+
+impl CommandExecutor for SInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, SetOp::Inter, &self.keys)
+    }
+}
+
+impl CommandExecutor for SUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, SetOp::Union, &self.keys)
+    }
+}
+
+impl CommandExecutor for SDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, SetOp::Diff, &self.keys)
+    }
+}
+
+impl CommandExecutor for SInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sets_combine_store(SetOp::Inter, self.destination, &self.keys))
+    }
+}
+
+impl CommandExecutor for SUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sets_combine_store(SetOp::Union, self.destination, &self.keys))
+    }
+}
+
+impl CommandExecutor for SDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sets_combine_store(SetOp::Diff, self.destination, &self.keys))
+    }
+}
+
+impl CommandExecutor for SInterCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.sintercard(&self.keys, self.limit))
+    }
+}
+
+fn combine_generic(backend: &Backend, op: SetOp, keys: &[String]) -> RespFrame {
+    match backend.sets_combine(op, keys) {
+        Ok(members) => set_reply(members),
+        Err(e) => e.into(),
+    }
+}
+
+fn set_reply(members: Vec<String>) -> RespFrame {
+    RespSet(
+        members
+            .into_iter()
+            .map(|member| BulkString(member.into_bytes()).into())
+            .collect(),
+    )
+    .into()
+}
+
+impl TryFrom<RespArray> for SAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members_args(value)?;
+        Ok(SAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members_args(value)?;
+        Ok(SRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMembers {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SIsMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SIsMember {
+            key: extract_string(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SMIsMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members_args(value)?;
+        Ok(SMIsMember { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // spop key [count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(count) => Some(usize::try_from(extract_i64(Some(count))?).map_err(|_| {
+                CommandError::InvalidArgument("value is out of range, must be positive".to_string())
+            })?),
+            None => None,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        Ok(SPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // srandmember key [count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(count) => Some(extract_random_count(Some(count))?),
+            None => None,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        Ok(SRandMember { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SInter {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SUnion {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SDiff {
+            keys: extract_strings(extract_args(value, 1)?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_members_args(value)?;
+        Ok(SInterStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_members_args(value)?;
+        Ok(SUnionStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_members_args(value)?;
+        Ok(SDiffStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // sintercard numkeys key [key ...] [LIMIT limit]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let numkeys = extract_i64(args.next())?;
+        if numkeys <= 0 {
+            return Err(CommandError::InvalidArgument(
+                "numkeys should be greater than 0".to_string(),
+            ));
+        }
+        if numkeys as usize > args.len() {
+            return Err(CommandError::InvalidArgument(
+                "Number of keys can't be greater than number of args".to_string(),
+            ));
+        }
+        let keys = extract_strings(args.by_ref().take(numkeys as usize).collect())?;
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let limit = match args.next() {
+            None => 0,
+            Some(option) if extract_string(Some(option.clone()))?.eq_ignore_ascii_case("limit") => {
+                usize::try_from(extract_i64(args.next())?).map_err(|_| {
+                    CommandError::InvalidArgument("LIMIT can't be negative".to_string())
+                })?
+            }
+            Some(_) => return Err(syntax_error()),
+        };
+        if args.next().is_some() {
+            return Err(syntax_error());
+        }
+        Ok(SInterCard { keys, limit })
+    }
+}
+
+// <cmd> key member [member ...], also <cmd> destination key [key ...]
+fn parse_members_args(value: RespArray) -> Result<(String, Vec<String>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    Ok((key, extract_strings(args.collect())?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        resp::{RespDecode, SimpleError},
+        test_util::{bulk, command},
+    };
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    // the members of a set or array reply, in no particular order
+    fn members(reply: RespFrame) -> HashSet<String> {
+        let items = match reply {
+            RespFrame::Set(set) => set.0,
+            RespFrame::Array(array) => array.0,
+            other => panic!("expected a set or an array, got {:?}", other),
+        };
+        items
+            .into_iter()
+            .map(|item| match item {
+                RespFrame::BulkString(s) => String::from_utf8_lossy(&s).to_string(),
+                other => panic!("expected a bulk string, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_set_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$4\r\nsadd\r\n$3\r\nset\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SAdd = frame.try_into()?;
+        assert_eq!(result.key, "set");
+        assert_eq!(result.members, strings(&["a", "b"]));
+
+        let mut buf = BytesMut::from("*3\r\n$4\r\nspop\r\n$3\r\nset\r\n$1\r\n3\r\n");
+        let result: SPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.count, Some(3));
+
+        let mut buf = BytesMut::from("*3\r\n$11\r\nsrandmember\r\n$3\r\nset\r\n$2\r\n-3\r\n");
+        let result: SRandMember = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.count, Some(-3));
+
+        let mut buf = BytesMut::from("*3\r\n$4\r\nspop\r\n$3\r\nset\r\n$2\r\n-1\r\n");
+        let err = SPop::try_from(RespArray::decode(&mut buf)?).unwrap_err();
+        assert_eq!(err.to_string(), "value is out of range, must be positive");
+
+        let mut buf =
+            BytesMut::from("*4\r\n$11\r\nsrandmember\r\n$3\r\nset\r\n$1\r\n1\r\n$1\r\nx\r\n");
+        let err = SRandMember::try_from(RespArray::decode(&mut buf)?).unwrap_err();
+        assert_eq!(err.to_string(), "syntax error");
+
+        let frame = command(&["srandmember", "set", "-1000000000"]);
+        let err = SRandMember::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "value is out of range");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_commands() -> Result<()> {
+        let backend = Backend::default();
+        let cmd = SAdd {
+            key: "set".to_string(),
+            members: strings(&["a", "b", "c", "a"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        let cmd = SMembers {
+            key: "set".to_string(),
+        };
+        let reply = cmd.execute(&backend);
+        assert!(matches!(reply, RespFrame::Set(_)));
+        assert_eq!(
+            members(reply),
+            strings(&["a", "b", "c"]).into_iter().collect()
+        );
+
+        let cmd = SIsMember {
+            key: "set".to_string(),
+            member: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = SMIsMember {
+            key: "set".to_string(),
+            members: strings(&["a", "x"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![RespFrame::Integer(1), RespFrame::Integer(0)]).into()
+        );
+        let cmd = SMIsMember {
+            key: "nosuchkey".to_string(),
+            members: strings(&["a"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![RespFrame::Integer(0)]).into()
+        );
+
+        let cmd = SRem {
+            key: "set".to_string(),
+            members: strings(&["a", "x"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = SCard {
+            key: "set".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        backend.set("string".to_string(), bulk("v"));
+        let cmd = SAdd {
+            key: "string".to_string(),
+            members: strings(&["a"]),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::Error(SimpleError(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ))
+        );
+        assert_eq!(backend.key_type("set"), Some("set"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_srandmember_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.sadd("set".to_string(), strings(&["a", "b", "c"]))?;
+        let all: HashSet<_> = strings(&["a", "b", "c"]).into_iter().collect();
+
+        let cmd = SRandMember {
+            key: "set".to_string(),
+            count: Some(5),
+        };
+        assert_eq!(members(cmd.execute(&backend)), all);
+        // a count past the size of the set is not allocated for
+        let cmd = SRandMember {
+            key: "set".to_string(),
+            count: Some(i64::MAX),
+        };
+        assert_eq!(members(cmd.execute(&backend)), all);
+        let cmd = SRandMember {
+            key: "set".to_string(),
+            count: Some(-5),
+        };
+        let RespFrame::Array(reply) = cmd.execute(&backend) else {
+            panic!("expected an array");
+        };
+        assert_eq!(reply.len(), 5);
+        let cmd = SRandMember {
+            key: "nosuchkey".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = SPop {
+            key: "set".to_string(),
+            count: None,
+        };
+        let RespFrame::BulkString(popped) = cmd.execute(&backend) else {
+            panic!("expected a bulk string");
+        };
+        let popped = String::from_utf8_lossy(&popped).to_string();
+        let cmd = SPop {
+            key: "set".to_string(),
+            count: Some(5),
+        };
+        let rest = members(cmd.execute(&backend));
+        assert_eq!(rest.len(), 2);
+        assert!(!rest.contains(&popped));
+        assert_eq!(backend.key_type("set"), None);
+
+        let all: Vec<_> = (0..10).map(|i| i.to_string()).collect();
+        backend.sadd("set".to_string(), all.clone())?;
+        let popped = backend.spop("set", 3)?;
+        assert_eq!(popped.iter().collect::<HashSet<_>>().len(), 3);
+        assert!(popped.iter().all(|member| all.contains(member)));
+        assert_eq!(backend.scard("set"), Ok(7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_smove_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.sadd("source".to_string(), strings(&["a", "b"]))?;
+        let smove = |source: &str, destination: &str, member: &str| {
+            SMove {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                member: member.to_string(),
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(smove("source", "destination", "a"), RespFrame::Integer(1));
+        assert_eq!(smove("source", "destination", "a"), RespFrame::Integer(0));
+        assert_eq!(backend.smembers("destination")?, strings(&["a"]));
+        assert_eq!(smove("source", "destination", "b"), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("source"), None);
+
+        // moving a member onto the same set keeps it
+        assert_eq!(
+            smove("destination", "destination", "a"),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(backend.scard("destination")?, 2);
+
+        backend.set("string".to_string(), bulk("v"));
+        assert!(matches!(
+            smove("destination", "string", "a"),
+            RespFrame::Error(_)
+        ));
+        assert!(matches!(
+            smove("destination", "string", "x"),
+            RespFrame::Error(_)
+        ));
+        assert_eq!(backend.scard("destination")?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nLIMIT\r\n$1\r\n5\r\n",
+        );
+        let result: SInterCard = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.keys, strings(&["a", "b"]));
+        assert_eq!(result.limit, 5);
+
+        let cases = [
+            (
+                "*3\r\n$10\r\nsintercard\r\n$1\r\n0\r\n$1\r\na\r\n",
+                "numkeys should be greater than 0",
+            ),
+            (
+                "*3\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n",
+                "Number of keys can't be greater than number of args",
+            ),
+            (
+                "*5\r\n$10\r\nsintercard\r\n$1\r\n1\r\n$1\r\na\r\n$5\r\nlimit\r\n$2\r\n-1\r\n",
+                "LIMIT can't be negative",
+            ),
+            (
+                "*4\r\n$10\r\nsintercard\r\n$1\r\n1\r\n$1\r\na\r\n$1\r\nb\r\n",
+                "syntax error",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = SInterCard::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_algebra_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.sadd("a".to_string(), strings(&["1", "2", "3", "4"]))?;
+        backend.sadd("b".to_string(), strings(&["2", "3", "5"]))?;
+        backend.sadd("c".to_string(), strings(&["3", "4", "5"]))?;
+        let set = |values: &[&str]| -> HashSet<String> { strings(values).into_iter().collect() };
+        let keys = |keys: &[&str]| strings(keys);
+
+        let reply = SInter {
+            keys: keys(&["a", "b"]),
+        }
+        .execute(&backend);
+        assert!(matches!(reply, RespFrame::Set(_)));
+        assert_eq!(members(reply), set(&["2", "3"]));
+        let reply = SInter {
+            keys: keys(&["a", "b", "nosuchkey"]),
+        }
+        .execute(&backend);
+        assert_eq!(members(reply), set(&[]));
+        let reply = SUnion {
+            keys: keys(&["a", "nosuchkey", "b"]),
+        }
+        .execute(&backend);
+        assert_eq!(members(reply), set(&["1", "2", "3", "4", "5"]));
+        let reply = SDiff {
+            keys: keys(&["a", "b", "c"]),
+        }
+        .execute(&backend);
+        assert_eq!(members(reply), set(&["1"]));
+        let reply = SDiff {
+            keys: keys(&["nosuchkey", "a"]),
+        }
+        .execute(&backend);
+        assert_eq!(members(reply), set(&[]));
+
+        // a key given twice is held twice at once
+        let reply = SInter {
+            keys: keys(&["a", "a"]),
+        }
+        .execute(&backend);
+        assert_eq!(members(reply), set(&["1", "2", "3", "4"]));
+
+        let cmd = SInterCard {
+            keys: keys(&["a", "b", "c"]),
+            limit: 0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = SInterCard {
+            keys: keys(&["a", "c"]),
+            limit: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        backend.set("string".to_string(), bulk("v"));
+        let reply = SUnion {
+            keys: keys(&["a", "string"]),
+        }
+        .execute(&backend);
+        assert!(matches!(reply, RespFrame::Error(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_algebra_store_commands() -> Result<()> {
+        let backend = Backend::default();
+        backend.sadd("a".to_string(), strings(&["1", "2", "3"]))?;
+        backend.sadd("b".to_string(), strings(&["2", "3", "4"]))?;
+
+        // the destination is overwritten whatever it held, deadline included
+        backend.set("dest".to_string(), bulk("v"));
+        backend.expire("dest", i64::MAX, Default::default());
+        let cmd = SInterStore {
+            destination: "dest".to_string(),
+            keys: strings(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.key_type("dest"), Some("set"));
+        assert_eq!(backend.expire_at("dest"), Some(None));
+
+        // a source may also be the destination
+        let cmd = SUnionStore {
+            destination: "a".to_string(),
+            keys: strings(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+        let cmd = SDiffStore {
+            destination: "dest".to_string(),
+            keys: strings(&["b", "a"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dest"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,519 @@
+use crate::{
+    backend::{
+        Backend, BackendError, StreamFields, StreamId, StreamTrim, TrimStrategy, XAddId,
+        XAddOptions,
+    },
+    resp::{BulkString, RespArray, RespFrame, RespNull},
+};
+
+use super::{
+    extract_args, extract_i64, extract_pairs, extract_string, integer_reply, CommandError,
+    CommandExecutor, XAdd, XDel, XLen, XRange, XRevRange, XTrim,
+};
+
+// same messages as redis
+const INVALID_STREAM_ID: &str = "Invalid stream ID specified as stream command argument";
+const LIMIT_WITHOUT_TRIM: &str =
+    "syntax error, LIMIT cannot be used without specifying a trimming strategy";
+const LIMIT_WITHOUT_APPROXIMATE: &str =
+    "syntax error, LIMIT cannot be used without the special ~ option";
+
+impl CommandExecutor for XAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xadd(self.key, self.id, self.fields, &self.options) {
+            Ok(Some(id)) => BulkString(id.to_string().into_bytes()).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        entries_reply(backend.xrange(&self.key, self.start, self.end, false, self.count))
+    }
+}
+
+impl CommandExecutor for XRevRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        entries_reply(backend.xrange(&self.key, self.start, self.end, true, self.count))
+    }
+}
+
+impl CommandExecutor for XLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.xlen(&self.key))
+    }
+}
+
+impl CommandExecutor for XTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.xtrim(&self.key, &self.trim))
+    }
+}
+
+impl CommandExecutor for XDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.xdel(&self.key, &self.ids))
+    }
+}
+
+// each entry as its ID followed by a flat array of its fields and values
+fn entries_reply(result: Result<Vec<(StreamId, StreamFields)>, BackendError>) -> RespFrame {
+    match result {
+        Ok(entries) => RespArray(
+            entries
+                .into_iter()
+                .map(|(id, fields)| {
+                    let mut items = Vec::with_capacity(fields.len() * 2);
+                    for (field, value) in fields {
+                        items.push(BulkString(field.into_bytes()).into());
+                        items.push(value);
+                    }
+                    RespArray(vec![
+                        BulkString(id.to_string().into_bytes()).into(),
+                        RespArray(items).into(),
+                    ])
+                    .into()
+                })
+                .collect(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+impl TryFrom<RespArray> for XAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // xadd key [NOMKSTREAM] [<MAXLEN | MINID> [= | ~] threshold [LIMIT count]]
+        //     <* | id> field value [field value ...]
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = extract_string(args.next())?;
+        let mut options = XAddOptions::default();
+        let mut limit = None;
+        while let Some(RespFrame::BulkString(arg)) = args.peek() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nomkstream" => {
+                    args.next();
+                    options.nomkstream = true;
+                }
+                b"maxlen" | b"minid" => {
+                    let strategy = extract_string(args.next())?;
+                    options.trim = Some(parse_trim(&strategy, &mut args)?);
+                }
+                b"limit" => {
+                    args.next();
+                    limit = Some(parse_limit(args.next())?);
+                }
+                _ => break,
+            }
+        }
+        options.trim = with_limit(options.trim, limit)?;
+        let id = parse_xadd_id(&extract_string(args.next())?)?;
+        let fields = extract_pairs(args.collect(), "xadd")?;
+        if fields.is_empty() {
+            return Err(CommandError::WrongArity("xadd".to_string()));
+        }
+        Ok(XAdd {
+            key,
+            id,
+            fields,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // xrange key start end [COUNT count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let start = parse_range_bound(args.next(), false)?;
+        let end = parse_range_bound(args.next(), true)?;
+        let count = parse_range_count(args)?;
+        Ok(XRange {
+            key,
+            start,
+            end,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XRevRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // xrevrange key end start [COUNT count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let end = parse_range_bound(args.next(), true)?;
+        let start = parse_range_bound(args.next(), false)?;
+        let count = parse_range_count(args)?;
+        Ok(XRevRange {
+            key,
+            start,
+            end,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(XLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // xtrim key <MAXLEN | MINID> [= | ~] threshold [LIMIT count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (mut trim, mut limit) = (None, None);
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?;
+            match option.to_ascii_lowercase().as_str() {
+                "maxlen" | "minid" => trim = Some(parse_trim(&option, &mut args)?),
+                "limit" => limit = Some(parse_limit(args.next())?),
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        let Some(trim) = with_limit(trim, limit)? else {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        };
+        Ok(XTrim { key, trim })
+    }
+}
+
+impl TryFrom<RespArray> for XDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let ids = args
+            .map(|id| parse_stream_id(&extract_string(Some(id))?, 0))
+            .collect::<Result<_, _>>()?;
+        Ok(XDel { key, ids })
+    }
+}
+
+// <ms>-<seq>, or <ms> alone with `seq` as the sequence number
+fn parse_stream_id(id: &str, seq: u64) -> Result<StreamId, CommandError> {
+    let parsed = match id.split_once('-') {
+        Some((ms, seq)) => ms.parse().ok().zip(seq.parse().ok()),
+        None => id.parse().ok().map(|ms| (ms, seq)),
+    };
+    match parsed {
+        Some((ms, seq)) => Ok(StreamId::new(ms, seq)),
+        None => Err(CommandError::InvalidArgument(INVALID_STREAM_ID.to_string())),
+    }
+}
+
+// `*`, `<ms>-*` or an ID greater than 0-0
+fn parse_xadd_id(id: &str) -> Result<XAddId, CommandError> {
+    if id == "*" {
+        return Ok(XAddId::Auto);
+    }
+    if let Some(ms) = id.strip_suffix("-*") {
+        return ms
+            .parse()
+            .map(XAddId::AutoSeq)
+            .map_err(|_| CommandError::InvalidArgument(INVALID_STREAM_ID.to_string()));
+    }
+    match parse_stream_id(id, 0)? {
+        StreamId::MIN => Err(CommandError::InvalidArgument(
+            "The ID specified in XADD must be greater than 0-0".to_string(),
+        )),
+        id => Ok(XAddId::Explicit(id)),
+    }
+}
+
+// `-`, `+` or an ID, exclusive when prefixed with `(`. An ID without a sequence
+// number starts at the first entry of its millisecond, or ends at the last one.
+fn parse_range_bound(frame: Option<RespFrame>, end: bool) -> Result<StreamId, CommandError> {
+    let bound = extract_string(frame)?;
+    let seq = if end { u64::MAX } else { 0 };
+    let Some(bound) = bound.strip_prefix('(') else {
+        return match bound.as_str() {
+            "-" => Ok(StreamId::MIN),
+            "+" => Ok(StreamId::MAX),
+            id => parse_stream_id(id, seq),
+        };
+    };
+    let id = parse_stream_id(bound, seq)?;
+    let (id, name) = if end {
+        (id.prev(), "end")
+    } else {
+        (id.next(), "start")
+    };
+    id.ok_or_else(|| CommandError::InvalidArgument(format!("invalid {} ID for the interval", name)))
+}
+
+// [COUNT count], a negative count returns nothing
+fn parse_range_count(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<Option<usize>, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let count = match args.next() {
+        None => None,
+        Some(option) if extract_string(Some(option.clone()))?.eq_ignore_ascii_case("count") => {
+            Some(usize::try_from(extract_i64(args.next())?).unwrap_or_default())
+        }
+        Some(_) => return Err(syntax_error()),
+    };
+    if args.next().is_some() {
+        return Err(syntax_error());
+    }
+    Ok(count)
+}
+
+// [= | ~] threshold, following the MAXLEN or MINID given as `strategy`
+fn parse_trim(
+    strategy: &str,
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<StreamTrim, CommandError> {
+    let mut threshold = extract_string(args.next())?;
+    let approximate = threshold == "~";
+    if approximate || threshold == "=" {
+        threshold = extract_string(args.next())?;
+    }
+    let strategy = if strategy.eq_ignore_ascii_case("maxlen") {
+        let max_len = threshold.parse::<i64>().map_err(|_| {
+            CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+        })?;
+        TrimStrategy::MaxLen(usize::try_from(max_len).map_err(|_| {
+            CommandError::InvalidArgument("The MAXLEN argument must be >= 0.".to_string())
+        })?)
+    } else {
+        TrimStrategy::MinId(parse_stream_id(&threshold, 0)?)
+    };
+    Ok(StreamTrim {
+        strategy,
+        approximate,
+        limit: None,
+    })
+}
+
+fn parse_limit(frame: Option<RespFrame>) -> Result<usize, CommandError> {
+    usize::try_from(extract_i64(frame)?)
+        .map_err(|_| CommandError::InvalidArgument("The LIMIT argument must be >= 0.".to_string()))
+}
+
+// LIMIT only goes with an approximate trim
+fn with_limit(
+    trim: Option<StreamTrim>,
+    limit: Option<usize>,
+) -> Result<Option<StreamTrim>, CommandError> {
+    match (trim, limit) {
+        (_, None) => Ok(trim),
+        (None, Some(_)) => Err(CommandError::InvalidArgument(
+            LIMIT_WITHOUT_TRIM.to_string(),
+        )),
+        (Some(trim), Some(_)) if !trim.approximate => Err(CommandError::InvalidArgument(
+            LIMIT_WITHOUT_APPROXIMATE.to_string(),
+        )),
+        (Some(trim), limit) => Ok(Some(StreamTrim { limit, ..trim })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        backend::MockClock,
+        test_util::{bulk, command},
+    };
+
+    use super::*;
+    use anyhow::Result;
+
+    fn entry(id: &str, fields: &[&str]) -> RespFrame {
+        RespArray(vec![
+            bulk(id),
+            RespArray(fields.iter().map(|f| bulk(f)).collect()).into(),
+        ])
+        .into()
+    }
+
+    #[test]
+    fn test_xadd_from_resp_array() -> Result<()> {
+        let cmd = XAdd::try_from(command(&[
+            "xadd",
+            "s",
+            "NOMKSTREAM",
+            "maxlen",
+            "~",
+            "10",
+            "limit",
+            "5",
+            "7-*",
+            "f",
+            "v",
+        ]))?;
+        assert_eq!(cmd.id, XAddId::AutoSeq(7));
+        assert_eq!(cmd.fields, vec![("f".to_string(), bulk("v"))]);
+        assert_eq!(
+            cmd.options,
+            XAddOptions {
+                nomkstream: true,
+                trim: Some(StreamTrim {
+                    strategy: TrimStrategy::MaxLen(10),
+                    approximate: true,
+                    limit: Some(5),
+                }),
+            }
+        );
+        let cmd = XAdd::try_from(command(&["xadd", "s", "minid", "5-1", "5", "f", "v"]))?;
+        assert_eq!(cmd.id, XAddId::Explicit(StreamId::new(5, 0)));
+        assert_eq!(
+            cmd.options.trim.map(|trim| trim.strategy),
+            Some(TrimStrategy::MinId(StreamId::new(5, 1)))
+        );
+
+        let cases: [(&[&str], &str); 7] = [
+            (
+                &["xadd", "s", "*", "f"],
+                "wrong number of arguments for 'xadd' command",
+            ),
+            (
+                &["xadd", "s", "maxlen", "1", "*"],
+                "wrong number of arguments for 'xadd' command",
+            ),
+            (
+                &["xadd", "s", "0-0", "f", "v"],
+                "The ID specified in XADD must be greater than 0-0",
+            ),
+            (&["xadd", "s", "1-x", "f", "v"], INVALID_STREAM_ID),
+            (
+                &["xadd", "s", "maxlen", "-1", "*", "f", "v"],
+                "The MAXLEN argument must be >= 0.",
+            ),
+            (
+                &["xadd", "s", "maxlen", "1", "limit", "1", "*", "f", "v"],
+                LIMIT_WITHOUT_APPROXIMATE,
+            ),
+            (
+                &["xadd", "s", "limit", "1", "*", "f", "v"],
+                LIMIT_WITHOUT_TRIM,
+            ),
+        ];
+        for (case, message) in cases {
+            let err = XAdd::try_from(command(case)).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+
+        let cmd = XRange::try_from(command(&["xrange", "s", "(5", "(6", "count", "2"]))?;
+        assert_eq!(
+            (cmd.start, cmd.end),
+            (StreamId::new(5, 1), StreamId::new(6, u64::MAX - 1))
+        );
+        assert_eq!(cmd.count, Some(2));
+        let cmd = XRevRange::try_from(command(&["xrevrange", "s", "+", "5"]))?;
+        assert_eq!((cmd.start, cmd.end), (StreamId::new(5, 0), StreamId::MAX));
+
+        let cases: [(&[&str], &str); 4] = [
+            (&["xrange", "s", "(-", "+"], INVALID_STREAM_ID),
+            (
+                &["xrange", "s", "-", "(0-0"],
+                "invalid end ID for the interval",
+            ),
+            (&["xrange", "s", "-", "+", "limit", "1"], "syntax error"),
+            (&["xtrim", "s", "limit", "1"], LIMIT_WITHOUT_TRIM),
+        ];
+        for (case, message) in cases {
+            let err = match case[0] {
+                "xrange" => XRange::try_from(command(case)).map(|_| ()),
+                _ => XTrim::try_from(command(case)).map(|_| ()),
+            }
+            .unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_commands() -> Result<()> {
+        let clock = Arc::new(MockClock::new(1_000));
+        let backend = Backend::with_clock(clock.clone());
+        let run = |args: &[&str]| -> Result<RespFrame> {
+            Ok(match args[0] {
+                "xadd" => XAdd::try_from(command(args))?.execute(&backend),
+                "xrange" => XRange::try_from(command(args))?.execute(&backend),
+                "xrevrange" => XRevRange::try_from(command(args))?.execute(&backend),
+                "xlen" => XLen::try_from(command(args))?.execute(&backend),
+                "xtrim" => XTrim::try_from(command(args))?.execute(&backend),
+                _ => XDel::try_from(command(args))?.execute(&backend),
+            })
+        };
+
+        assert_eq!(
+            run(&["xadd", "s", "nomkstream", "*", "f", "v"])?,
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(backend.key_type("s"), None);
+        assert_eq!(run(&["xadd", "s", "*", "a", "1"])?, bulk("1000-0"));
+        assert_eq!(run(&["xadd", "s", "*", "b", "2"])?, bulk("1000-1"));
+        clock.advance(5);
+        assert_eq!(
+            run(&["xadd", "s", "*", "c", "3", "d", "4"])?,
+            bulk("1005-0")
+        );
+        assert_eq!(run(&["xadd", "s", "2000", "e", "5"])?, bulk("2000-0"));
+        assert!(matches!(
+            run(&["xadd", "s", "1999-*", "f", "v"])?,
+            RespFrame::Error(_)
+        ));
+        assert_eq!(backend.key_type("s"), Some("stream"));
+        assert_eq!(run(&["xlen", "s"])?, 4.into());
+
+        assert_eq!(
+            run(&["xrange", "s", "(1000-0", "1005"])?,
+            RespArray(vec![
+                entry("1000-1", &["b", "2"]),
+                entry("1005-0", &["c", "3", "d", "4"])
+            ])
+            .into()
+        );
+        assert_eq!(
+            run(&["xrevrange", "s", "+", "-", "count", "1"])?,
+            RespArray(vec![entry("2000-0", &["e", "5"])]).into()
+        );
+        assert_eq!(
+            run(&["xrange", "none", "-", "+"])?,
+            RespArray(vec![]).into()
+        );
+
+        assert_eq!(run(&["xdel", "s", "1000-1", "3000"])?, 1.into());
+        assert_eq!(run(&["xtrim", "s", "maxlen", "=", "2"])?, 1.into());
+        assert_eq!(run(&["xtrim", "s", "maxlen", "~", "0"])?, 0.into());
+        assert_eq!(run(&["xtrim", "s", "minid", "2001"])?, 2.into());
+        // an empty stream is kept, and still remembers its last ID
+        assert_eq!(run(&["xlen", "s"])?, 0.into());
+        assert_eq!(backend.key_type("s"), Some("stream"));
+        assert!(matches!(
+            run(&["xadd", "s", "2000-0", "f", "v"])?,
+            RespFrame::Error(_)
+        ));
+        assert_eq!(
+            run(&["xadd", "s", "maxlen", "0", "*", "f", "v"])?,
+            bulk("2000-1")
+        );
+        assert_eq!(run(&["xlen", "s"])?, 0.into());
+
+        backend.set("string".to_string(), bulk("v"));
+        assert!(matches!(
+            run(&["xadd", "string", "*", "f", "v"])?,
+            RespFrame::Error(_)
+        ));
+        assert!(matches!(run(&["xlen", "string"])?, RespFrame::Error(_)));
+        Ok(())
+    }
+}
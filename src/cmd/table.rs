@@ -0,0 +1,1044 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::resp::{BulkString, RespArray, RespFrame, RespMap, SimpleString};
+
+use super::{
+    Append, BLMPop, BLMove, BLPop, BRPop, BZMPop, BZPopMax, BZPopMin, Command, CommandCmd,
+    CommandError, DbSize, Decr, DecrBy, Del, Exists, Expire, ExpireAt, FlushAll, FlushDb, Get,
+    GetDel, GetEx, GetRange, GetSet, HDel, HExists, HExpire, HGet, HGetAll, HGetEx, HIncrBy,
+    HIncrByFloat, HKeys, HLen, HMGet, HMSet, HPExpire, HPersist, HRandField, HScan, HSet, HSetNx,
+    HStrlen, HTtl, HVals, Hello, Incr, IncrBy, IncrByFloat, Keys, LIndex, LInsert, LLen, LMPop,
+    LMove, LPop, LPos, LPush, LRange, LRem, LSet, LTrim, MGet, MSet, MSetNx, PExpire, PExpireAt,
+    PSetEx, PTtl, Persist, RPop, RPopLPush, RPush, RandomKey, Rename, RenameNx, SAdd, SCard, SDiff,
+    SDiffStore, SInter, SInterCard, SInterStore, SIsMember, SMIsMember, SMembers, SMove, SPop,
+    SRandMember, SRem, SUnion, SUnionStore, Scan, Set, SetEx, SetNx, SetRange, Strlen, Ttl, Type,
+    Unlink, XAdd, XDel, XLen, XRange, XRevRange, XTrim, ZAdd, ZCard, ZCount, ZDiff, ZDiffStore,
+    ZIncrBy, ZInter, ZInterStore, ZLexCount, ZMPop, ZPopMax, ZPopMin, ZRange, ZRangeByLex,
+    ZRangeByScore, ZRangeStore, ZRank, ZRem, ZRevRange, ZRevRangeByLex, ZRevRangeByScore, ZRevRank,
+    ZScore, ZUnion, ZUnionStore,
+};
+
+use CommandFlag::*;
+use CommandGroup as G;
+
+type Parser = fn(RespArray) -> Result<Command, CommandError>;
+
+/// Everything the server knows about a command: how to parse it and what
+/// COMMAND reports about it.
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// number of arguments including the command name, -N means at least N
+    pub arity: i64,
+    pub flags: &'static [CommandFlag],
+    pub first_key: i64,
+    /// negative values count from the end of the arguments
+    pub last_key: i64,
+    pub step: i64,
+    /// position of an argument giving the number of keys right after it,
+    /// for the keys first/last/step can't describe
+    pub numkeys: Option<usize>,
+    pub group: CommandGroup,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub parse: Parser,
+}
+
+// not every flag is used by the commands implemented so far
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandFlag {
+    Write,
+    ReadOnly,
+    DenyOom,
+    Fast,
+    Blocking,
+    PubSub,
+    MovableKeys,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandGroup {
+    Generic,
+    String,
+    Hash,
+    List,
+    Set,
+    SortedSet,
+    Stream,
+    Server,
+    Connection,
+}
+
+lazy_static! {
+    static ref COMMAND_TABLE: HashMap<&'static str, CommandSpec> = [
+        spec("get", 2, parse::<Get>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "1.0.0", "Returns the string value of a key."),
+        spec("set", -3, parse::<Set>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "1.0.0",
+                "Sets the string value of a key, ignoring its type."
+            ),
+        spec("getset", 3, parse::<GetSet>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "1.0.0", "Returns the previous string value of a key after setting it to a new value."),
+        spec("getdel", 2, parse::<GetDel>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "6.2.0", "Returns the string value of a key after deleting the key."),
+        spec("getex", -2, parse::<GetEx>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "6.2.0", "Returns the string value of a key after setting its expiration time."),
+        spec("setnx", 3, parse::<SetNx>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "1.0.0", "Set the string value of a key only when the key doesn't exist."),
+        spec("setex", 4, parse::<SetEx>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(G::String, "2.0.0", "Sets the string value and expiration time of a key."),
+        spec("psetex", 4, parse::<PSetEx>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(G::String, "2.6.0", "Sets both string value and expiration time in milliseconds of a key."),
+        spec("mget", -2, parse::<MGet>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, -1, 1)
+            .docs(G::String, "1.0.0", "Atomically returns the string values of one or more keys."),
+        spec("mset", -3, parse::<MSet>)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 2)
+            .docs(G::String, "1.0.1", "Atomically creates or modifies the string values of one or more keys."),
+        spec("msetnx", -3, parse::<MSetNx>)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 2)
+            .docs(G::String, "1.0.1", "Atomically modifies the string values of one or more keys only when all keys don't exist."),
+        spec("incr", 2, parse::<Incr>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "1.0.0",
+                "Increments the integer value of a key by one."
+            ),
+        spec("decr", 2, parse::<Decr>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "1.0.0",
+                "Decrements the integer value of a key by one."
+            ),
+        spec("incrby", 3, parse::<IncrBy>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "1.0.0",
+                "Increments the integer value of a key by a number."
+            ),
+        spec("decrby", 3, parse::<DecrBy>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "1.0.0",
+                "Decrements a number from the integer value of a key."
+            ),
+        spec("incrbyfloat", 3, parse::<IncrByFloat>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "2.6.0",
+                "Increment the floating point value of a key by a number."
+            ),
+        spec("append", 3, parse::<Append>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "2.0.0",
+                "Appends a string to the value of a key."
+            ),
+        spec("strlen", 2, parse::<Strlen>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::String, "2.2.0", "Returns the length of a string value."),
+        spec("getrange", 4, parse::<GetRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "2.4.0",
+                "Returns a substring of the string stored at a key."
+            ),
+        spec("setrange", 4, parse::<SetRange>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(
+                G::String,
+                "2.2.0",
+                "Overwrites a part of a string value with another by an offset."
+            ),
+        spec("hget", 3, parse::<HGet>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns the value of a field in a hash."),
+        spec("hset", -4, parse::<HSet>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Hash,
+                "2.0.0",
+                "Creates or modifies the value of a field in a hash."
+            ),
+        spec("hgetall", 2, parse::<HGetAll>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns all fields and values in a hash."),
+        spec("hdel", -3, parse::<HDel>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain."),
+        spec("hexists", 3, parse::<HExists>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Determines whether a field exists in a hash."),
+        spec("hlen", 2, parse::<HLen>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns the number of fields in a hash."),
+        spec("hkeys", 2, parse::<HKeys>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns all fields in a hash."),
+        spec("hvals", 2, parse::<HVals>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns all values in a hash."),
+        spec("hmget", -3, parse::<HMGet>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Returns the values of all fields in a hash."),
+        spec("hmset", -4, parse::<HMSet>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Sets the values of multiple fields."),
+        spec("hsetnx", 4, parse::<HSetNx>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Sets the value of a field in a hash only when the field doesn't exist."),
+        spec("hstrlen", 3, parse::<HStrlen>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "3.2.0", "Returns the length of the value of a field."),
+        spec("hincrby", 4, parse::<HIncrBy>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.0.0", "Increments the integer value of a field in a hash by a number."),
+        spec("hincrbyfloat", 4, parse::<HIncrByFloat>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.6.0", "Increments the floating point value of a field by a number."),
+        spec("hrandfield", -2, parse::<HRandField>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "6.2.0", "Returns one or more random fields from a hash."),
+        spec("hscan", -3, parse::<HScan>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "2.8.0", "Iterates over fields and values of a hash."),
+        spec("hexpire", -6, parse::<HExpire>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "7.4.0", "Set expiry for hash field using relative time to expire (seconds)."),
+        spec("hpexpire", -6, parse::<HPExpire>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "7.4.0", "Set expiry for hash field using relative time to expire (milliseconds)."),
+        spec("httl", -5, parse::<HTtl>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "7.4.0", "Returns the TTL in seconds of a hash field."),
+        spec("hpersist", -5, parse::<HPersist>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "7.4.0", "Removes the expiration time for each specified field."),
+        spec("hgetex", -5, parse::<HGetEx>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Hash, "8.0.0", "Get the value of one or more fields of a given hash key, and optionally set their expiration."),
+        spec("lpush", -3, parse::<LPush>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Prepends one or more elements to a list. Creates the key if it doesn't exist."),
+        spec("rpush", -3, parse::<RPush>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Appends one or more elements to a list. Creates the key if it doesn't exist."),
+        spec("lpop", -2, parse::<LPop>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Returns the first elements in a list after removing it. Deletes the list if the last element was popped."),
+        spec("rpop", -2, parse::<RPop>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Returns and removes the last elements of a list. Deletes the list if the last element was popped."),
+        spec("lrange", 4, parse::<LRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Returns a range of elements from a list."),
+        spec("llen", 2, parse::<LLen>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Returns the length of a list."),
+        spec("lindex", 3, parse::<LIndex>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Returns an element from a list by its index."),
+        spec("lset", 4, parse::<LSet>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Sets the value of an element in a list by its index."),
+        spec("linsert", 5, parse::<LInsert>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .docs(G::List, "2.2.0", "Inserts an element before or after another element in a list."),
+        spec("lrem", 4, parse::<LRem>)
+            .flags(&[Write])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Removes elements from a list. Deletes the list if the last element was removed."),
+        spec("ltrim", 4, parse::<LTrim>)
+            .flags(&[Write])
+            .keys(1, 1, 1)
+            .docs(G::List, "1.0.0", "Removes elements from both ends a list. Deletes the list if all elements were trimmed."),
+        spec("lmove", 5, parse::<LMove>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 2, 1)
+            .docs(G::List, "6.2.0", "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved."),
+        spec("rpoplpush", 3, parse::<RPopLPush>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 2, 1)
+            .docs(G::List, "1.2.0", "Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped."),
+        spec("lmpop", -4, parse::<LMPop>)
+            .flags(&[Write, MovableKeys])
+            .numkeys(1)
+            .docs(G::List, "7.0.0", "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped."),
+        spec("lpos", -3, parse::<LPos>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::List, "6.0.6", "Returns the index of matching elements in a list."),
+        spec("blpop", -3, parse::<BLPop>)
+            .flags(&[Write, Blocking])
+            .keys(1, -2, 1)
+            .docs(G::List, "2.0.0", "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+        spec("brpop", -3, parse::<BRPop>)
+            .flags(&[Write, Blocking])
+            .keys(1, -2, 1)
+            .docs(G::List, "2.0.0", "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+        spec("blmove", 6, parse::<BLMove>)
+            .flags(&[Write, DenyOom, Blocking])
+            .keys(1, 2, 1)
+            .docs(G::List, "6.2.0", "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved."),
+        spec("blmpop", -5, parse::<BLMPop>)
+            .flags(&[Write, Blocking, MovableKeys])
+            .numkeys(2)
+            .docs(G::List, "7.0.0", "Pops the first element from one of multiple lists. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+        spec("sadd", -3, parse::<SAdd>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Adds one or more members to a set. Creates the key if it doesn't exist."),
+        spec("srem", -3, parse::<SRem>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Removes one or more members from a set. Deletes the set if the last member was removed."),
+        spec("smembers", 2, parse::<SMembers>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Returns all members of a set."),
+        spec("sismember", 3, parse::<SIsMember>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Determines whether a member belongs to a set."),
+        spec("smismember", -3, parse::<SMIsMember>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "6.2.0", "Determines whether multiple members belong to a set."),
+        spec("scard", 2, parse::<SCard>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Returns the number of members in a set."),
+        spec("spop", -2, parse::<SPop>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Returns one or more random members from a set after removing them. Deletes the set if the last member was popped."),
+        spec("srandmember", -2, parse::<SRandMember>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Set, "1.0.0", "Returns one or more random members from a set."),
+        spec("smove", 4, parse::<SMove>)
+            .flags(&[Write, Fast])
+            .keys(1, 2, 1)
+            .docs(G::Set, "1.0.0", "Moves a member from one set to another."),
+        spec("sinter", -2, parse::<SInter>)
+            .flags(&[ReadOnly])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Returns the intersect of multiple sets."),
+        spec("sunion", -2, parse::<SUnion>)
+            .flags(&[ReadOnly])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Returns the union of multiple sets."),
+        spec("sdiff", -2, parse::<SDiff>)
+            .flags(&[ReadOnly])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Returns the difference of multiple sets."),
+        spec("sinterstore", -3, parse::<SInterStore>)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Stores the intersect of multiple sets in a key."),
+        spec("sunionstore", -3, parse::<SUnionStore>)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Stores the union of multiple sets in a key."),
+        spec("sdiffstore", -3, parse::<SDiffStore>)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 1)
+            .docs(G::Set, "1.0.0", "Stores the difference of multiple sets in a key."),
+        spec("sintercard", -3, parse::<SInterCard>)
+            .flags(&[ReadOnly, MovableKeys])
+            .numkeys(1)
+            .docs(G::Set, "7.0.0", "Returns the number of members of the intersect of multiple sets."),
+        spec("zadd", -4, parse::<ZAdd>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist."),
+        spec("zrem", -3, parse::<ZRem>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed."),
+        spec("zscore", 3, parse::<ZScore>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Returns the score of a member in a sorted set."),
+        spec("zincrby", 4, parse::<ZIncrBy>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Increments the score of a member in a sorted set."),
+        spec("zcard", 2, parse::<ZCard>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Returns the number of members in a sorted set."),
+        spec("zrank", -3, parse::<ZRank>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.0.0", "Returns the index of a member in a sorted set ordered by ascending scores."),
+        spec("zrevrank", -3, parse::<ZRevRank>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.2.0", "Returns the index of a member in a sorted set ordered by descending scores."),
+        spec("zrange", -4, parse::<ZRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Returns members in a sorted set within a range of indexes."),
+        spec("zrangestore", -5, parse::<ZRangeStore>)
+            .flags(&[Write, DenyOom])
+            .keys(1, 2, 1)
+            .docs(G::SortedSet, "6.2.0", "Stores a range of members from sorted set in a key."),
+        spec("zrevrange", -4, parse::<ZRevRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.2.0", "Returns members in a sorted set within a range of indexes in reverse order."),
+        spec("zrangebyscore", -4, parse::<ZRangeByScore>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "1.0.5", "Returns members in a sorted set within a range of scores."),
+        spec("zrevrangebyscore", -4, parse::<ZRevRangeByScore>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.2.0", "Returns members in a sorted set within a range of scores in reverse order."),
+        spec("zrangebylex", -4, parse::<ZRangeByLex>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.8.9", "Returns members in a sorted set within a lexicographical range."),
+        spec("zrevrangebylex", -4, parse::<ZRevRangeByLex>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.8.9", "Returns members in a sorted set within a lexicographical range in reverse order."),
+        spec("zcount", 4, parse::<ZCount>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.0.0", "Returns the count of members in a sorted set that have scores within a range."),
+        spec("zlexcount", 4, parse::<ZLexCount>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "2.8.9", "Returns the number of members in a sorted set within a lexicographical range."),
+        spec("zunion", -3, parse::<ZUnion>)
+            .flags(&[ReadOnly, MovableKeys])
+            .numkeys(1)
+            .docs(G::SortedSet, "6.2.0", "Returns the union of multiple sorted sets."),
+        spec("zinter", -3, parse::<ZInter>)
+            .flags(&[ReadOnly, MovableKeys])
+            .numkeys(1)
+            .docs(G::SortedSet, "6.2.0", "Returns the intersect of multiple sorted sets."),
+        spec("zdiff", -3, parse::<ZDiff>)
+            .flags(&[ReadOnly, MovableKeys])
+            .numkeys(1)
+            .docs(G::SortedSet, "6.2.0", "Returns the difference between multiple sorted sets."),
+        spec("zunionstore", -4, parse::<ZUnionStore>)
+            .flags(&[Write, DenyOom, MovableKeys])
+            .keys(1, 1, 1)
+            .numkeys(2)
+            .docs(G::SortedSet, "2.0.0", "Stores the union of multiple sorted sets in a key."),
+        spec("zinterstore", -4, parse::<ZInterStore>)
+            .flags(&[Write, DenyOom, MovableKeys])
+            .keys(1, 1, 1)
+            .numkeys(2)
+            .docs(G::SortedSet, "2.0.0", "Stores the intersect of multiple sorted sets in a key."),
+        spec("zdiffstore", -4, parse::<ZDiffStore>)
+            .flags(&[Write, DenyOom, MovableKeys])
+            .keys(1, 1, 1)
+            .numkeys(2)
+            .docs(G::SortedSet, "6.2.0", "Stores the difference of multiple sorted sets in a key."),
+        spec("zpopmin", -2, parse::<ZPopMin>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "5.0.0", "Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+        spec("zpopmax", -2, parse::<ZPopMax>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::SortedSet, "5.0.0", "Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+        spec("zmpop", -4, parse::<ZMPop>)
+            .flags(&[Write, MovableKeys])
+            .numkeys(1)
+            .docs(G::SortedSet, "7.0.0", "Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped."),
+        spec("bzpopmin", -3, parse::<BZPopMin>)
+            .flags(&[Write, Fast, Blocking])
+            .keys(1, -2, 1)
+            .docs(G::SortedSet, "5.0.0", "Removes and returns the member with the lowest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+        spec("bzpopmax", -3, parse::<BZPopMax>)
+            .flags(&[Write, Fast, Blocking])
+            .keys(1, -2, 1)
+            .docs(G::SortedSet, "5.0.0", "Removes and returns the member with the highest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+        spec("bzmpop", -5, parse::<BZMPop>)
+            .flags(&[Write, Blocking, MovableKeys])
+            .numkeys(2)
+            .docs(G::SortedSet, "7.0.0", "Removes and returns a member by score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+        spec("xadd", -5, parse::<XAdd>)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Appends a new message to a stream. Creates the key if it doesn't exist."),
+        spec("xrange", -4, parse::<XRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Returns the messages from a stream within a range of IDs."),
+        spec("xrevrange", -4, parse::<XRevRange>)
+            .flags(&[ReadOnly])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Returns the messages from a stream within a range of IDs in reverse order."),
+        spec("xlen", 2, parse::<XLen>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Return the number of messages in a stream."),
+        spec("xtrim", -4, parse::<XTrim>)
+            .flags(&[Write])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Deletes messages from the beginning of a stream."),
+        spec("xdel", -3, parse::<XDel>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Stream, "5.0.0", "Returns the number of messages after removing them from a stream."),
+        spec("expire", -3, parse::<Expire>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Sets the expiration time of a key in seconds."
+            ),
+        spec("pexpire", -3, parse::<PExpire>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "2.6.0",
+                "Sets the expiration time of a key in milliseconds."
+            ),
+        spec("expireat", -3, parse::<ExpireAt>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "1.2.0",
+                "Sets the expiration time of a key to a Unix timestamp."
+            ),
+        spec("pexpireat", -3, parse::<PExpireAt>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "2.6.0",
+                "Sets the expiration time of a key to a Unix ms timestamp."
+            ),
+        spec("ttl", 2, parse::<Ttl>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Returns the expiration time in seconds of a key."
+            ),
+        spec("pttl", 2, parse::<PTtl>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "2.6.0",
+                "Returns the expiration time in milliseconds of a key."
+            ),
+        spec("persist", 2, parse::<Persist>)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .docs(G::Generic, "2.2.0", "Removes the expiration time of a key."),
+        spec("type", 2, parse::<Type>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, 1, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Determines the type of value stored at a key."
+            ),
+        spec("del", -2, parse::<Del>)
+            .flags(&[Write])
+            .keys(1, -1, 1)
+            .docs(G::Generic, "1.0.0", "Deletes one or more keys."),
+        spec("unlink", -2, parse::<Unlink>)
+            .flags(&[Write, Fast])
+            .keys(1, -1, 1)
+            .docs(
+                G::Generic,
+                "4.0.0",
+                "Asynchronously deletes one or more keys."
+            ),
+        spec("exists", -2, parse::<Exists>)
+            .flags(&[ReadOnly, Fast])
+            .keys(1, -1, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Determines whether one or more keys exist."
+            ),
+        spec("keys", 2, parse::<Keys>).flags(&[ReadOnly]).docs(
+            G::Generic,
+            "1.0.0",
+            "Returns all key names that match a pattern."
+        ),
+        spec("scan", -2, parse::<Scan>).flags(&[ReadOnly]).docs(
+            G::Generic,
+            "2.8.0",
+            "Iterates over the key names in the database."
+        ),
+        spec("rename", 3, parse::<Rename>)
+            .flags(&[Write])
+            .keys(1, 2, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Renames a key and overwrites the destination."
+            ),
+        spec("renamenx", 3, parse::<RenameNx>)
+            .flags(&[Write, Fast])
+            .keys(1, 2, 1)
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Renames a key only when the target key name doesn't exist."
+            ),
+        spec("randomkey", 1, parse::<RandomKey>)
+            .flags(&[ReadOnly])
+            .docs(
+                G::Generic,
+                "1.0.0",
+                "Returns a random key name from the database."
+            ),
+        spec("dbsize", 1, parse::<DbSize>)
+            .flags(&[ReadOnly, Fast])
+            .docs(
+                G::Server,
+                "1.0.0",
+                "Returns the number of keys in the database."
+            ),
+        spec("flushdb", -1, parse::<FlushDb>).flags(&[Write]).docs(
+            G::Server,
+            "1.0.0",
+            "Removes all keys from the current database."
+        ),
+        spec("flushall", -1, parse::<FlushAll>)
+            .flags(&[Write])
+            .docs(G::Server, "1.0.0", "Removes all keys from all databases."),
+        spec("command", -1, parse::<CommandCmd>).docs(
+            G::Server,
+            "2.8.13",
+            "Returns detailed information about all commands."
+        ),
+        spec("hello", -1, parse::<Hello>)
+            .flags(&[Fast])
+            .docs(G::Connection, "6.0.0", "Handshakes with the Redis server."),
+    ]
+    .into_iter()
+    .map(|spec| (spec.name, spec))
+    .collect();
+}
+
+/// Find a command by its lowercase name.
+pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE.get(name)
+}
+
+/// All commands, sorted by name.
+pub fn all() -> Vec<&'static CommandSpec> {
+    let mut specs: Vec<_> = COMMAND_TABLE.values().collect();
+    specs.sort_by_key(|spec| spec.name);
+    specs
+}
+
+fn spec(name: &'static str, arity: i64, parse: Parser) -> CommandSpec {
+    CommandSpec {
+        name,
+        arity,
+        flags: &[],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        numkeys: None,
+        group: CommandGroup::Generic,
+        since: "",
+        summary: "",
+        parse,
+    }
+}
+
+fn parse<T>(value: RespArray) -> Result<Command, CommandError>
+where
+    T: TryFrom<RespArray, Error = CommandError> + Into<Command>,
+{
+    Ok(T::try_from(value)?.into())
+}
+
+impl CommandSpec {
+    fn flags(self, flags: &'static [CommandFlag]) -> Self {
+        Self { flags, ..self }
+    }
+
+    fn keys(self, first_key: i64, last_key: i64, step: i64) -> Self {
+        Self {
+            first_key,
+            last_key,
+            step,
+            ..self
+        }
+    }
+
+    /// The argument at `numkeys` gives the number of keys that follow it.
+    fn numkeys(self, numkeys: usize) -> Self {
+        Self {
+            numkeys: Some(numkeys),
+            ..self
+        }
+    }
+
+    fn docs(self, group: CommandGroup, since: &'static str, summary: &'static str) -> Self {
+        Self {
+            group,
+            since,
+            summary,
+            ..self
+        }
+    }
+
+    /// `argc` includes the command name.
+    pub fn check_arity(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity >= 0 {
+            argc == self.arity
+        } else {
+            argc >= -self.arity
+        }
+    }
+
+    /// Positions of the keys in `args` (which include the command name), None if
+    /// the number of keys given in the arguments is not valid.
+    pub fn key_positions(&self, args: &[RespFrame]) -> Option<Vec<usize>> {
+        let argc = args.len();
+        let mut positions = vec![];
+        if self.first_key > 0 && self.step > 0 {
+            let last = if self.last_key < 0 {
+                argc as i64 + self.last_key
+            } else {
+                self.last_key
+            };
+            positions.extend(
+                (self.first_key..=last.min(argc as i64 - 1))
+                    .step_by(self.step as usize)
+                    .map(|i| i as usize),
+            );
+        }
+        if let Some(index) = self.numkeys {
+            let Some(RespFrame::BulkString(numkeys)) = args.get(index) else {
+                return None;
+            };
+            let numkeys: usize = std::str::from_utf8(numkeys).ok()?.parse().ok()?;
+            if numkeys == 0 || numkeys > argc - index - 1 {
+                return None;
+            }
+            positions.extend(index + 1..=index + numkeys);
+        }
+        Some(positions)
+    }
+
+    /// The reply of COMMAND INFO for this command.
+    pub fn info(&self) -> RespFrame {
+        let flags = self
+            .flags
+            .iter()
+            .map(|flag| SimpleString(flag.as_str().to_string()).into())
+            .collect();
+        RespArray(vec![
+            BulkString(self.name.into()).into(),
+            self.arity.into(),
+            RespArray(flags).into(),
+            self.first_key.into(),
+            self.last_key.into(),
+            self.step.into(),
+            RespArray(self.acl_categories()).into(),
+            // tips, key specifications and subcommands are not supported
+            RespArray(vec![]).into(),
+            RespArray(vec![]).into(),
+            RespArray(vec![]).into(),
+        ])
+        .into()
+    }
+
+    /// The reply of COMMAND DOCS for this command.
+    pub fn docs_reply(&self) -> RespFrame {
+        let mut docs = RespMap(HashMap::new());
+        docs.insert(
+            "summary".to_string(),
+            BulkString(self.summary.into()).into(),
+        );
+        docs.insert("since".to_string(), BulkString(self.since.into()).into());
+        docs.insert(
+            "group".to_string(),
+            BulkString(self.group.as_str().into()).into(),
+        );
+        docs.into()
+    }
+
+    fn acl_categories(&self) -> Vec<RespFrame> {
+        let mut categories = vec![];
+        for flag in self.flags {
+            match flag {
+                Write => categories.push("@write"),
+                ReadOnly => categories.push("@read"),
+                Fast => categories.push("@fast"),
+                Blocking => categories.push("@blocking"),
+                PubSub => categories.push("@pubsub"),
+                DenyOom | MovableKeys => {}
+            }
+        }
+        if !self.flags.contains(&Fast) {
+            categories.push("@slow");
+        }
+        categories.push(match self.group {
+            CommandGroup::Generic => "@keyspace",
+            CommandGroup::String => "@string",
+            CommandGroup::Hash => "@hash",
+            CommandGroup::List => "@list",
+            CommandGroup::Set => "@set",
+            CommandGroup::SortedSet => "@sortedset",
+            CommandGroup::Stream => "@stream",
+            CommandGroup::Server | CommandGroup::Connection => "@connection",
+        });
+        categories
+            .into_iter()
+            .map(|category| SimpleString(category.to_string()).into())
+            .collect()
+    }
+}
+
+impl CommandFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Write => "write",
+            ReadOnly => "readonly",
+            DenyOom => "denyoom",
+            Fast => "fast",
+            Blocking => "blocking",
+            PubSub => "pubsub",
+            MovableKeys => "movablekeys",
+        }
+    }
+}
+
+impl CommandGroup {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandGroup::Generic => "generic",
+            CommandGroup::String => "string",
+            CommandGroup::Hash => "hash",
+            CommandGroup::List => "list",
+            CommandGroup::Set => "set",
+            CommandGroup::SortedSet => "sorted-set",
+            CommandGroup::Stream => "stream",
+            CommandGroup::Server => "server",
+            CommandGroup::Connection => "connection",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command;
+
+    #[test]
+    fn test_lookup_is_case_insensitive() -> anyhow::Result<()> {
+        let cmd: Command = command(&["GET", "hello"]).try_into()?;
+        assert!(matches!(cmd, Command::Get(_)));
+
+        let cmd: Command = command(&["hGetAll", "map"]).try_into()?;
+        assert!(matches!(cmd, Command::HGetAll(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let err = Command::try_from(command(&["foo", "bar", "baz"])).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            RespFrame::Error(crate::resp::SimpleError(
+                "ERR unknown command 'foo', with args beginning with: 'bar' 'baz' ".to_string()
+            ))
+        );
+
+        let err = Command::try_from(command(&["foo"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown command 'foo', with args beginning with: "
+        );
+
+        let long = "x".repeat(200);
+        let err = Command::try_from(command(&["foo", &long, "next"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "unknown command 'foo', with args beginning with: '{}' ",
+                "x".repeat(128)
+            )
+        );
+    }
+
+    #[test]
+    fn test_arity() {
+        let get = lookup("get").unwrap();
+        assert!(get.check_arity(2));
+        assert!(!get.check_arity(1));
+        assert!(!get.check_arity(3));
+
+        let set = lookup("set").unwrap();
+        assert!(!set.check_arity(2));
+        assert!(set.check_arity(3));
+        assert!(set.check_arity(7));
+
+        let err = Command::try_from(command(&["GET"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'get' command"
+        );
+        let err = Command::try_from(command(&["hset", "k", "f"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'hset' command"
+        );
+    }
+
+    #[test]
+    fn test_key_positions() {
+        let get = lookup("get").unwrap();
+        assert_eq!(get.key_positions(&command(&["get", "k"])), Some(vec![1]));
+
+        let spec = spec("mget", -2, parse::<Get>).keys(1, -1, 1);
+        assert_eq!(
+            spec.key_positions(&command(&["mget", "a", "b", "c"])),
+            Some(vec![1, 2, 3])
+        );
+        let spec = spec.keys(1, -1, 2);
+        assert_eq!(
+            spec.key_positions(&command(&["mset", "a", "1", "b", "2"])),
+            Some(vec![1, 3])
+        );
+
+        let command_spec = lookup("command").unwrap();
+        assert_eq!(
+            command_spec.key_positions(&command(&["command", "count"])),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_numkeys_key_positions() {
+        let spec = CommandSpec {
+            numkeys: Some(1),
+            ..spec("lmpop", -4, parse::<Get>)
+        };
+        assert_eq!(
+            spec.key_positions(&command(&["lmpop", "2", "a", "b", "left"])),
+            Some(vec![2, 3])
+        );
+        // a destination before the number of keys
+        let spec = CommandSpec {
+            numkeys: Some(2),
+            ..spec.keys(1, 1, 1)
+        };
+        assert_eq!(
+            spec.key_positions(&command(&["zunionstore", "d", "2", "a", "b"])),
+            Some(vec![1, 3, 4])
+        );
+        for numkeys in ["0", "-1", "x", "3"] {
+            let args = command(&["zunionstore", "d", numkeys, "a", "b"]);
+            assert_eq!(spec.key_positions(&args), None, "{}", numkeys);
+        }
+    }
+
+    #[test]
+    fn test_command_info() {
+        let info = lookup("get").unwrap().info();
+        let RespFrame::Array(info) = info else {
+            panic!("COMMAND INFO must reply an array");
+        };
+        assert_eq!(info.len(), 10);
+        assert_eq!(info[0], BulkString("get".into()).into());
+        assert_eq!(info[1], 2.into());
+        assert_eq!(
+            info[2],
+            RespArray(vec![
+                SimpleString("readonly".into()).into(),
+                SimpleString("fast".into()).into(),
+            ])
+            .into()
+        );
+        assert_eq!(info[3], 1.into());
+        assert_eq!(info[4], 1.into());
+        assert_eq!(info[5], 1.into());
+    }
+}
@@ -0,0 +1,1656 @@
+use std::{ops::Bound, time::Duration};
+
+use crate::{
+    backend::{
+        Aggregate, Backend, BackendError, LexBound, ServeFn, SetCondition, SetOp, ZAddOptions,
+        ZCombineOptions, ZRangeBy, ZRangeSpec, ZSetEnd,
+    },
+    resp::{BulkString, RespArray, RespFrame, RespNull},
+};
+
+use super::{
+    blocking::{serve_now, BlockingCommand},
+    extract_args, extract_f64, extract_i64, extract_string, extract_strings, extract_timeout,
+    integer_reply, BZMPop, BZPopMax, BZPopMin, CommandError, CommandExecutor, ZAdd, ZCard, ZCount,
+    ZDiff, ZDiffStore, ZIncrBy, ZInter, ZInterStore, ZLexCount, ZMPop, ZPopMax, ZPopMin, ZRange,
+    ZRangeByLex, ZRangeByScore, ZRangeStore, ZRank, ZRem, ZRevRange, ZRevRangeByLex,
+    ZRevRangeByScore, ZRevRank, ZScore, ZUnion, ZUnionStore,
+};
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if !self.incr {
+            return integer_reply(backend.zadd(self.key, self.pairs, self.options));
+        }
+        // the parser makes sure INCR comes with a single pair
+        let Some((increment, member)) = self.pairs.into_iter().next() else {
+            return RespFrame::Null(RespNull);
+        };
+        score_reply(backend.zincr_by(self.key, member, increment, self.options))
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = backend.zincr_by(self.key, self.member, self.increment, Default::default());
+        score_reply(result)
+    }
+}
+
+impl CommandExecutor for ZRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zrem(&self.key, &self.members))
+    }
+}
+
+impl CommandExecutor for ZScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        score_reply(backend.zscore(&self.key, &self.member))
+    }
+}
+
+impl CommandExecutor for ZCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zcard(&self.key))
+    }
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        rank_reply(
+            backend.zrank(&self.key, &self.member, false),
+            self.with_score,
+        )
+    }
+}
+
+impl CommandExecutor for ZRevRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        rank_reply(
+            backend.zrank(&self.key, &self.member, true),
+            self.with_score,
+        )
+    }
+}
+
+impl CommandExecutor for ZRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRevRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRevRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRangeByLex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRevRangeByLex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range_reply(backend.zrange(&self.key, &self.spec), self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZRangeStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zrange_store(self.destination, &self.key, &self.spec))
+    }
+}
+
+impl CommandExecutor for ZCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zcount(&self.key, &self.spec))
+    }
+}
+
+impl CommandExecutor for ZLexCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zcount(&self.key, &self.spec))
+    }
+}
+
+impl CommandExecutor for ZUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = backend.zsets_combine(SetOp::Union, &self.keys, &self.options);
+        range_reply(result, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = backend.zsets_combine(SetOp::Inter, &self.keys, &self.options);
+        range_reply(result, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = backend.zsets_combine(SetOp::Diff, &self.keys, &Default::default());
+        range_reply(result, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zsets_combine_store(
+            SetOp::Union,
+            self.destination,
+            &self.keys,
+            &self.options,
+        ))
+    }
+}
+
+impl CommandExecutor for ZInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zsets_combine_store(
+            SetOp::Inter,
+            self.destination,
+            &self.keys,
+            &self.options,
+        ))
+    }
+}
+
+impl CommandExecutor for ZDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        integer_reply(backend.zsets_combine_store(
+            SetOp::Diff,
+            self.destination,
+            &self.keys,
+            &Default::default(),
+        ))
+    }
+}
+
+impl CommandExecutor for ZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop_generic(backend, &self.key, ZSetEnd::Min, self.count)
+    }
+}
+
+impl CommandExecutor for ZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop_generic(backend, &self.key, ZSetEnd::Max, self.count)
+    }
+}
+
+impl CommandExecutor for ZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zmpop(&self.keys, self.end, self.count) {
+            Ok(Some((key, members))) => mpop_reply(&key, members),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl CommandExecutor for BZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl CommandExecutor for BZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        serve_now(&self, backend)
+    }
+}
+
+impl BlockingCommand for BZPopMin {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        serve_zpop(ZSetEnd::Min)
+    }
+}
+
+impl BlockingCommand for BZPopMax {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        serve_zpop(ZSetEnd::Max)
+    }
+}
+
+impl BlockingCommand for BZMPop {
+    fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn serve(&self) -> ServeFn {
+        let (end, count) = (self.end, self.count);
+        Box::new(move |backend, key| {
+            Ok(backend
+                .zpop(key, end, count)?
+                .map(|members| mpop_reply(key, members)))
+        })
+    }
+}
+
+// pop one member, replied as key, member, score
+fn serve_zpop(end: ZSetEnd) -> ServeFn {
+    Box::new(move |backend, key| {
+        Ok(backend
+            .zpop(key, end, 1)?
+            .and_then(|mut members| members.pop())
+            .map(|(member, score)| {
+                RespArray(vec![
+                    BulkString(key.into()).into(),
+                    BulkString(member.into_bytes()).into(),
+                    RespFrame::Double(score),
+                ])
+                .into()
+            }))
+    })
+}
+
+// members and their scores in a flat array, also without a count
+fn zpop_generic(backend: &Backend, key: &str, end: ZSetEnd, count: Option<usize>) -> RespFrame {
+    let result = backend
+        .zpop(key, end, count.unwrap_or(1))
+        .map(Option::unwrap_or_default);
+    range_reply(result, true)
+}
+
+// the key popped from, then a [member, score] pair per member
+fn mpop_reply(key: &str, members: Vec<(String, f64)>) -> RespFrame {
+    let members = members
+        .into_iter()
+        .map(|(member, score)| {
+            RespArray(vec![
+                BulkString(member.into_bytes()).into(),
+                RespFrame::Double(score),
+            ])
+            .into()
+        })
+        .collect();
+    RespArray(vec![
+        BulkString(key.into()).into(),
+        RespArray(members).into(),
+    ])
+    .into()
+}
+
+// scores are doubles, which RESP2 clients get as bulk strings
+fn score_reply(result: Result<Option<f64>, BackendError>) -> RespFrame {
+    match result {
+        Ok(Some(score)) => RespFrame::Double(score),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+fn rank_reply(result: Result<Option<(usize, f64)>, BackendError>, with_score: bool) -> RespFrame {
+    match result {
+        Ok(Some((rank, score))) if with_score => RespArray(vec![
+            RespFrame::Integer(rank as i64),
+            RespFrame::Double(score),
+        ])
+        .into(),
+        Ok(Some((rank, _))) => RespFrame::Integer(rank as i64),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+// members, each followed by its score with WITHSCORES
+fn range_reply(result: Result<Vec<(String, f64)>, BackendError>, with_scores: bool) -> RespFrame {
+    match result {
+        Ok(members) => {
+            let mut reply = Vec::with_capacity(members.len() * (1 + with_scores as usize));
+            for (member, score) in members {
+                reply.push(BulkString(member.into_bytes()).into());
+                if with_scores {
+                    reply.push(RespFrame::Double(score));
+                }
+            }
+            RespArray(reply).into()
+        }
+        Err(e) => e.into(),
+    }
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // zadd key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = extract_string(args.next())?;
+        let mut options = ZAddOptions::default();
+        let (mut nx, mut xx, mut incr) = (false, false, false);
+        while let Some(RespFrame::BulkString(arg)) = args.peek() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nx" => nx = true,
+                b"xx" => xx = true,
+                b"gt" => options.gt = true,
+                b"lt" => options.lt = true,
+                b"ch" => options.ch = true,
+                b"incr" => incr = true,
+                _ => break,
+            }
+            args.next();
+        }
+        let args: Vec<_> = args.collect();
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        if nx && xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if [nx, options.gt, options.lt]
+            .iter()
+            .filter(|&&set| set)
+            .count()
+            > 1
+        {
+            return Err(CommandError::InvalidArgument(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if incr && args.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+        options.condition = match (nx, xx) {
+            (true, _) => SetCondition::IfNotExists,
+            (_, true) => SetCondition::IfExists,
+            _ => SetCondition::Always,
+        };
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        let mut args = args.into_iter();
+        while let Some(score) = args.next() {
+            pairs.push((extract_f64(Some(score))?, extract_string(args.next())?));
+        }
+        Ok(ZAdd {
+            key,
+            options,
+            incr,
+            pairs,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZIncrBy {
+            key: extract_string(args.next())?,
+            increment: extract_f64(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        Ok(ZRem {
+            key,
+            members: extract_strings(args.collect())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZScore {
+            key: extract_string(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value)?;
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value)?;
+        Ok(ZRevRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+// <cmd> key member [WITHSCORE]
+fn parse_rank_args(value: RespArray) -> Result<(String, String, bool), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let member = extract_string(args.next())?;
+    let with_score = match args.next() {
+        Some(arg) if args.len() == 0 => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("withscore") {
+                return Err(CommandError::InvalidArgument("syntax error".to_string()));
+            }
+            true
+        }
+        Some(_) => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        None => false,
+    };
+    Ok((key, member, with_score))
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // zrange key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, None, false)?;
+        Ok(ZRange {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // zrangestore dst src min max [BYSCORE | BYLEX] [REV] [LIMIT offset count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let key = extract_string(args.next())?;
+        let (spec, _) = parse_range_args(args, None, true)?;
+        Ok(ZRangeStore {
+            destination,
+            key,
+            spec,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, Some((RangeType::Rank, true)), false)?;
+        Ok(ZRevRange {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, Some((RangeType::Score, false)), false)?;
+        Ok(ZRangeByScore {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRangeByScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, Some((RangeType::Score, true)), false)?;
+        Ok(ZRevRangeByScore {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByLex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, Some((RangeType::Lex, false)), false)?;
+        Ok(ZRangeByLex {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRangeByLex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (spec, with_scores) = parse_range_args(args, Some((RangeType::Lex, true)), false)?;
+        Ok(ZRevRangeByLex {
+            key,
+            spec,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let min = parse_score_bound(args.next())?;
+        let max = parse_score_bound(args.next())?;
+        Ok(ZCount {
+            key,
+            spec: ZRangeSpec {
+                by: ZRangeBy::Score(min, max),
+                rev: false,
+                limit: None,
+            },
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZLexCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let min = parse_lex_bound(args.next())?;
+        let max = parse_lex_bound(args.next())?;
+        Ok(ZLexCount {
+            key,
+            spec: ZRangeSpec {
+                by: ZRangeBy::Lex(min, max),
+                rev: false,
+                limit: None,
+            },
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, options, with_scores) = parse_combine_args(args, "zunion", SetOp::Union, false)?;
+        Ok(ZUnion {
+            keys,
+            options,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, options, with_scores) = parse_combine_args(args, "zinter", SetOp::Inter, false)?;
+        Ok(ZInter {
+            keys,
+            options,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, _, with_scores) = parse_combine_args(args, "zdiff", SetOp::Diff, false)?;
+        Ok(ZDiff { keys, with_scores })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let (keys, options, _) = parse_combine_args(args, "zunionstore", SetOp::Union, true)?;
+        Ok(ZUnionStore {
+            destination,
+            keys,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let (keys, options, _) = parse_combine_args(args, "zinterstore", SetOp::Inter, true)?;
+        Ok(ZInterStore {
+            destination,
+            keys,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let (keys, _, _) = parse_combine_args(args, "zdiffstore", SetOp::Diff, true)?;
+        Ok(ZDiffStore { destination, keys })
+    }
+}
+
+// numkeys key [key ...] [WEIGHTS weight [weight ...]] [AGGREGATE SUM | MIN | MAX] [WITHSCORES],
+// returning the keys, the options and whether WITHSCORES was given. ZDIFF takes neither
+// WEIGHTS nor AGGREGATE, the STORE variants take no WITHSCORES.
+fn parse_combine_args(
+    mut args: std::vec::IntoIter<RespFrame>,
+    name: &str,
+    op: SetOp,
+    store: bool,
+) -> Result<(Vec<String>, ZCombineOptions, bool), CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let numkeys = extract_i64(args.next())?;
+    if numkeys <= 0 {
+        return Err(CommandError::InvalidArgument(format!(
+            "at least 1 input key is needed for '{}' command",
+            name
+        )));
+    }
+    let numkeys = numkeys as usize;
+    if numkeys > args.len() {
+        return Err(syntax_error());
+    }
+    let keys = extract_strings(args.by_ref().take(numkeys).collect())?;
+    let mut options = ZCombineOptions::default();
+    let mut with_scores = false;
+    while let Some(arg) = args.next() {
+        let option = extract_string(Some(arg))?.to_ascii_lowercase();
+        match option.as_str() {
+            "weights" if op != SetOp::Diff && args.len() >= numkeys => {
+                options.weights = args
+                    .by_ref()
+                    .take(numkeys)
+                    .map(|weight| {
+                        extract_f64(Some(weight)).map_err(|_| {
+                            CommandError::InvalidArgument("weight value is not a float".to_string())
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "aggregate" if op != SetOp::Diff => {
+                let aggregate = extract_string(args.next()).map_err(|_| syntax_error())?;
+                options.aggregate = match aggregate.to_ascii_lowercase().as_str() {
+                    "sum" => Aggregate::Sum,
+                    "min" => Aggregate::Min,
+                    "max" => Aggregate::Max,
+                    _ => return Err(syntax_error()),
+                };
+            }
+            "withscores" if !store => with_scores = true,
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok((keys, options, with_scores))
+}
+
+impl TryFrom<RespArray> for ZPopMin {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_zpop_args(value)?;
+        Ok(ZPopMin { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMax {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_zpop_args(value)?;
+        Ok(ZPopMax { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // zmpop numkeys key [key ...] <MIN | MAX> [COUNT count]
+        let (keys, end, count) = parse_zmpop_args(extract_args(value, 1)?)?;
+        Ok(ZMPop { keys, end, count })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMin {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_bzpop_args(value)?;
+        Ok(BZPopMin { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMax {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_bzpop_args(value)?;
+        Ok(BZPopMax { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BZMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // bzmpop timeout numkeys key [key ...] <MIN | MAX> [COUNT count]
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = extract_timeout(args.next())?;
+        let (keys, end, count) = parse_zmpop_args(args.collect())?;
+        Ok(BZMPop {
+            timeout,
+            keys,
+            end,
+            count,
+        })
+    }
+}
+
+// <cmd> key [count]
+fn parse_zpop_args(value: RespArray) -> Result<(String, Option<usize>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(count) => Some(usize::try_from(extract_i64(Some(count))?).map_err(|_| {
+            CommandError::InvalidArgument("value is out of range, must be positive".to_string())
+        })?),
+        None => None,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok((key, count))
+}
+
+// <cmd> key [key ...] timeout
+fn parse_bzpop_args(value: RespArray) -> Result<(Vec<String>, Option<Duration>), CommandError> {
+    let mut args = extract_args(value, 1)?;
+    let timeout = extract_timeout(args.pop())?;
+    Ok((extract_strings(args)?, timeout))
+}
+
+// numkeys key [key ...] <MIN | MAX> [COUNT count]
+fn parse_zmpop_args(args: Vec<RespFrame>) -> Result<(Vec<String>, ZSetEnd, usize), CommandError> {
+    let mut args = args.into_iter();
+    let numkeys = extract_i64(args.next())?;
+    if numkeys <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    if numkeys as usize >= args.len() {
+        return Err(syntax_error());
+    }
+    let keys = extract_strings(args.by_ref().take(numkeys as usize).collect())?;
+    let end = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+        "min" => ZSetEnd::Min,
+        "max" => ZSetEnd::Max,
+        _ => return Err(syntax_error()),
+    };
+    let count = match args.next() {
+        None => 1,
+        Some(option) if extract_string(Some(option.clone()))?.eq_ignore_ascii_case("count") => {
+            let count = extract_i64(args.next())?;
+            if count <= 0 {
+                return Err(CommandError::InvalidArgument(
+                    "count should be greater than 0".to_string(),
+                ));
+            }
+            count as usize
+        }
+        Some(_) => return Err(syntax_error()),
+    };
+    if args.next().is_some() {
+        return Err(syntax_error());
+    }
+    Ok((keys, end, count))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RangeType {
+    Rank,
+    Score,
+    Lex,
+}
+
+// start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES], returning the
+// range and whether WITHSCORES was given. The legacy commands fix the type and the
+// direction of the range and only take LIMIT and WITHSCORES, ZRANGESTORE takes no
+// WITHSCORES.
+fn parse_range_args(
+    mut args: impl Iterator<Item = RespFrame>,
+    legacy: Option<(RangeType, bool)>,
+    store: bool,
+) -> Result<(ZRangeSpec, bool), CommandError> {
+    let syntax_error = |msg: &str| CommandError::InvalidArgument(msg.to_string());
+    let start = args.next();
+    let stop = args.next();
+    let (mut range_type, mut rev) = legacy.unwrap_or((RangeType::Rank, false));
+    let mut limit = None;
+    let mut with_scores = false;
+    while let Some(arg) = args.next() {
+        match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+            "byscore" if legacy.is_none() => range_type = RangeType::Score,
+            "bylex" if legacy.is_none() => range_type = RangeType::Lex,
+            "rev" if legacy.is_none() => rev = true,
+            "withscores" if !store => with_scores = true,
+            "limit" => {
+                let offset = extract_i64(args.next())?;
+                let count = extract_i64(args.next())?;
+                // a negative offset selects nothing, a negative count everything after it
+                limit = Some(match (usize::try_from(offset), usize::try_from(count)) {
+                    (Err(_), _) => (0, 0),
+                    (Ok(offset), Ok(count)) => (offset, count),
+                    (Ok(offset), Err(_)) => (offset, usize::MAX),
+                });
+            }
+            _ => return Err(syntax_error("syntax error")),
+        }
+    }
+    if limit.is_some() && range_type == RangeType::Rank {
+        return Err(syntax_error(
+            "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX",
+        ));
+    }
+    if with_scores && range_type == RangeType::Lex {
+        return Err(syntax_error(
+            "syntax error, WITHSCORES not supported in combination with BYLEX",
+        ));
+    }
+    // reversed ranges by score or by member are given as max min
+    let (min, max) = match (range_type, rev) {
+        (RangeType::Score | RangeType::Lex, true) => (stop, start),
+        _ => (start, stop),
+    };
+    let by = match range_type {
+        RangeType::Rank => ZRangeBy::Rank(extract_i64(min)?, extract_i64(max)?),
+        RangeType::Score => ZRangeBy::Score(parse_score_bound(min)?, parse_score_bound(max)?),
+        RangeType::Lex => ZRangeBy::Lex(parse_lex_bound(min)?, parse_lex_bound(max)?),
+    };
+    Ok((ZRangeSpec { by, rev, limit }, with_scores))
+}
+
+// a score, exclusive when prefixed with `(`
+fn parse_score_bound(frame: Option<RespFrame>) -> Result<Bound<f64>, CommandError> {
+    let bound = extract_string(frame)?;
+    let (bound, exclusive) = match bound.strip_prefix('(') {
+        Some(bound) => (bound, true),
+        None => (bound.as_str(), false),
+    };
+    match bound.parse::<f64>() {
+        Ok(score) if !score.is_nan() && exclusive => Ok(Bound::Excluded(score)),
+        Ok(score) if !score.is_nan() => Ok(Bound::Included(score)),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max is not a float".to_string(),
+        )),
+    }
+}
+
+// `-`, `+`, `[member` or `(member`
+fn parse_lex_bound(frame: Option<RespFrame>) -> Result<LexBound, CommandError> {
+    let bound = extract_string(frame)?;
+    match bound.as_bytes().first() {
+        Some(b'-') if bound.len() == 1 => Ok(LexBound::Min),
+        Some(b'+') if bound.len() == 1 => Ok(LexBound::Max),
+        Some(b'[') => Ok(LexBound::Included(bound[1..].to_string())),
+        Some(b'(') => Ok(LexBound::Excluded(bound[1..].to_string())),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max not valid string range item".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cmd::{Command, Session},
+        resp::{Protocol, RespDecode},
+        test_util::{bulk, command},
+    };
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn zadd(backend: &Backend, options: &str, pairs: &[(f64, &str)]) -> RespFrame {
+        let mut cmd = ZAdd {
+            key: "zset".to_string(),
+            options: ZAddOptions::default(),
+            incr: false,
+            pairs: pairs.iter().map(|(s, m)| (*s, m.to_string())).collect(),
+        };
+        for option in options.split_whitespace() {
+            match option {
+                "nx" => cmd.options.condition = SetCondition::IfNotExists,
+                "xx" => cmd.options.condition = SetCondition::IfExists,
+                "gt" => cmd.options.gt = true,
+                "lt" => cmd.options.lt = true,
+                "ch" => cmd.options.ch = true,
+                "incr" => cmd.incr = true,
+                _ => panic!("unknown option {}", option),
+            }
+        }
+        cmd.execute(backend)
+    }
+
+    #[test]
+    fn test_zadd_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*8\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$2\r\nXX\r\n$2\r\nch\r\n$3\r\n1.5\r\n$1\r\na\r\n$4\r\n-inf\r\n$1\r\nb\r\n",
+        );
+        let result: ZAdd = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.key, "zset");
+        assert_eq!(result.options.condition, SetCondition::IfExists);
+        assert!(result.options.ch && !result.incr);
+        assert_eq!(
+            result.pairs,
+            vec![(1.5, "a".to_string()), (f64::NEG_INFINITY, "b".to_string())]
+        );
+
+        let cases = [
+            ("*4\r\n$4\r\nzadd\r\n$1\r\nz\r\n$2\r\nnx\r\n$1\r\n1\r\n", "syntax error"),
+            (
+                "*6\r\n$4\r\nzadd\r\n$1\r\nz\r\n$2\r\nnx\r\n$2\r\nxx\r\n$1\r\n1\r\n$1\r\na\r\n",
+                "XX and NX options at the same time are not compatible",
+            ),
+            (
+                "*6\r\n$4\r\nzadd\r\n$1\r\nz\r\n$2\r\ngt\r\n$2\r\nlt\r\n$1\r\n1\r\n$1\r\na\r\n",
+                "GT, LT, and/or NX options at the same time are not compatible",
+            ),
+            (
+                "*7\r\n$4\r\nzadd\r\n$1\r\nz\r\n$4\r\nincr\r\n$1\r\n1\r\n$1\r\na\r\n$1\r\n2\r\n$1\r\nb\r\n",
+                "INCR option supports a single increment-element pair",
+            ),
+            (
+                "*4\r\n$4\r\nzadd\r\n$1\r\nz\r\n$3\r\nnan\r\n$1\r\na\r\n",
+                "value is not a valid float",
+            ),
+        ];
+        for (case, message) in cases {
+            let frame = RespArray::decode(&mut BytesMut::from(case))?;
+            let err = ZAdd::try_from(frame).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+
+        let mut buf =
+            BytesMut::from("*4\r\n$5\r\nzrank\r\n$4\r\nzset\r\n$1\r\na\r\n$9\r\nWITHSCORE\r\n");
+        let result: ZRank = RespArray::decode(&mut buf)?.try_into()?;
+        assert!(result.with_score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_options() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(zadd(&backend, "", &[(1.0, "a"), (2.0, "b")]), 2.into());
+        assert_eq!(zadd(&backend, "nx", &[(5.0, "a"), (3.0, "c")]), 1.into());
+        assert_eq!(backend.zscore("zset", "a")?, Some(1.0));
+        assert_eq!(zadd(&backend, "xx", &[(5.0, "a"), (4.0, "d")]), 0.into());
+        assert_eq!(backend.zscore("zset", "a")?, Some(5.0));
+        assert_eq!(backend.zscore("zset", "d")?, None);
+
+        // CH counts the updated members, not the ones left unchanged
+        assert_eq!(
+            zadd(&backend, "ch", &[(5.0, "a"), (1.0, "b"), (4.0, "d")]),
+            2.into()
+        );
+        assert_eq!(
+            zadd(&backend, "gt ch", &[(4.0, "a"), (2.0, "b"), (0.0, "e")]),
+            2.into()
+        );
+        assert_eq!(backend.zscore("zset", "a")?, Some(5.0));
+        assert_eq!(backend.zscore("zset", "b")?, Some(2.0));
+        assert_eq!(zadd(&backend, "lt ch", &[(6.0, "a"), (1.0, "b")]), 1.into());
+        assert_eq!(backend.zcard("zset")?, 5);
+
+        assert_eq!(
+            zadd(&backend, "incr", &[(1.5, "a")]),
+            RespFrame::Double(6.5)
+        );
+        assert_eq!(
+            zadd(&backend, "incr nx", &[(1.0, "a")]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            zadd(&backend, "incr gt", &[(-1.0, "a")]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            zadd(&backend, "incr xx", &[(1.0, "x")]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(backend.zscore("zset", "x")?, None);
+
+        // XX on a missing key doesn't create it
+        let cmd = ZAdd {
+            key: "other".to_string(),
+            options: ZAddOptions {
+                condition: SetCondition::IfExists,
+                ..Default::default()
+            },
+            incr: false,
+            pairs: vec![(1.0, "a".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend), 0.into());
+        assert_eq!(backend.key_type("other"), None);
+        assert_eq!(backend.key_type("zset"), Some("zset"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zset_commands() -> Result<()> {
+        let backend = Backend::default();
+        zadd(&backend, "", &[(1.0, "a"), (2.0, "b"), (2.0, "c")]);
+
+        let cmd = ZScore {
+            key: "zset".to_string(),
+            member: "b".to_string(),
+        };
+        let reply = cmd.execute(&backend);
+        assert_eq!(reply, RespFrame::Double(2.0));
+        assert_eq!(Protocol::Resp2.reply(reply), bulk("2"));
+
+        let cmd = ZIncrBy {
+            key: "zset".to_string(),
+            increment: -0.5,
+            member: "c".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(1.5));
+        let cmd = ZIncrBy {
+            key: "zset".to_string(),
+            increment: f64::INFINITY,
+            member: "a".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(f64::INFINITY));
+        let cmd = ZIncrBy {
+            key: "zset".to_string(),
+            increment: f64::NEG_INFINITY,
+            member: "a".to_string(),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+
+        // c 1.5, b 2, a inf
+        let rank = |member: &str, with_score| {
+            ZRank {
+                key: "zset".to_string(),
+                member: member.to_string(),
+                with_score,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(rank("c", false), 0.into());
+        assert_eq!(rank("a", false), 2.into());
+        assert_eq!(rank("x", false), RespFrame::Null(RespNull));
+        assert_eq!(
+            rank("b", true),
+            RespArray(vec![1.into(), RespFrame::Double(2.0)]).into()
+        );
+        let cmd = ZRevRank {
+            key: "zset".to_string(),
+            member: "a".to_string(),
+            with_score: false,
+        };
+        assert_eq!(cmd.execute(&backend), 0.into());
+
+        let cmd = ZRem {
+            key: "zset".to_string(),
+            members: vec!["a".to_string(), "x".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), 1.into());
+        let cmd = ZCard {
+            key: "zset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), 2.into());
+
+        backend.set("string".to_string(), bulk("v"));
+        let cmd = ZCard {
+            key: "string".to_string(),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_from_resp_array() -> Result<()> {
+        let cmd = ZRange::try_from(command(&[
+            "zrange",
+            "zset",
+            "(5",
+            "-inf",
+            "BYSCORE",
+            "rev",
+            "limit",
+            "1",
+            "-1",
+            "withscores",
+        ]))?;
+        assert_eq!(
+            cmd.spec,
+            ZRangeSpec {
+                by: ZRangeBy::Score(Bound::Included(f64::NEG_INFINITY), Bound::Excluded(5.0)),
+                rev: true,
+                limit: Some((1, usize::MAX)),
+            }
+        );
+        assert!(cmd.with_scores);
+
+        // ranks aren't swapped by REV, and the last of BYSCORE / BYLEX wins
+        let cmd = ZRange::try_from(command(&["zrange", "zset", "0", "-1", "rev"]))?;
+        assert_eq!(cmd.spec.by, ZRangeBy::Rank(0, -1));
+        let cmd = ZRange::try_from(command(&["zrange", "z", "-", "[b", "byscore", "bylex"]))?;
+        assert_eq!(
+            cmd.spec.by,
+            ZRangeBy::Lex(LexBound::Min, LexBound::Included("b".to_string()))
+        );
+        let cmd = ZRevRangeByLex::try_from(command(&["zrevrangebylex", "z", "+", "(a"]))?;
+        assert_eq!(
+            cmd.spec.by,
+            ZRangeBy::Lex(LexBound::Excluded("a".to_string()), LexBound::Max)
+        );
+        assert!(cmd.spec.rev);
+        let cmd = ZRangeStore::try_from(command(&["zrangestore", "dst", "src", "0", "1"]))?;
+        assert_eq!((cmd.destination.as_str(), cmd.key.as_str()), ("dst", "src"));
+
+        let cases: [(&[&str], &str); 8] = [
+            (
+                &["zrange", "z", "0", "1", "limit", "0", "1"],
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX",
+            ),
+            (
+                &["zrange", "z", "-", "+", "bylex", "withscores"],
+                "syntax error, WITHSCORES not supported in combination with BYLEX",
+            ),
+            (&["zrange", "z", "0", "1", "byscore", "foo"], "syntax error"),
+            (
+                &["zrangestore", "d", "z", "0", "1", "withscores"],
+                "syntax error",
+            ),
+            (&["zrangebyscore", "z", "0", "1", "rev"], "syntax error"),
+            (
+                &["zrangebyscore", "z", "(", "1"],
+                "min or max is not a float",
+            ),
+            (&["zcount", "z", "nan", "1"], "min or max is not a float"),
+            (
+                &["zlexcount", "z", "a", "+"],
+                "min or max not valid string range item",
+            ),
+        ];
+        for (case, message) in cases {
+            let err = match case[0] {
+                "zrange" => ZRange::try_from(command(case)).map(|_| ()),
+                "zrangestore" => ZRangeStore::try_from(command(case)).map(|_| ()),
+                "zrangebyscore" => ZRangeByScore::try_from(command(case)).map(|_| ()),
+                "zcount" => ZCount::try_from(command(case)).map(|_| ()),
+                _ => ZLexCount::try_from(command(case)).map(|_| ()),
+            }
+            .unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_commands() -> Result<()> {
+        let backend = Backend::default();
+        zadd(
+            &backend,
+            "",
+            &[(1.0, "a"), (2.0, "b"), (2.5, "c"), (3.0, "d")],
+        );
+
+        let reply = ZRange::try_from(command(&["zrange", "zset", "1", "2", "withscores"]))?
+            .execute(&backend);
+        assert_eq!(
+            Protocol::Resp2.reply(reply),
+            RespArray(vec![bulk("b"), bulk("2"), bulk("c"), bulk("2.5")]).into()
+        );
+        let reply =
+            ZRevRange::try_from(command(&["zrevrange", "zset", "0", "1"]))?.execute(&backend);
+        assert_eq!(reply, RespArray(vec![bulk("d"), bulk("c")]).into());
+        let reply = ZRevRangeByScore::try_from(command(&[
+            "zrevrangebyscore",
+            "zset",
+            "+inf",
+            "(1",
+            "limit",
+            "1",
+            "5",
+        ]))?
+        .execute(&backend);
+        assert_eq!(reply, RespArray(vec![bulk("c"), bulk("b")]).into());
+        let reply = ZRangeByScore::try_from(command(&["zrangebyscore", "zset", "5", "+inf"]))?
+            .execute(&backend);
+        assert_eq!(reply, RespArray(vec![]).into());
+
+        let reply = ZCount::try_from(command(&["zcount", "zset", "(1", "2.5"]))?.execute(&backend);
+        assert_eq!(reply, 2.into());
+        let reply =
+            ZCount::try_from(command(&["zcount", "none", "-inf", "+inf"]))?.execute(&backend);
+        assert_eq!(reply, 0.into());
+
+        let reply = ZRangeStore::try_from(command(&[
+            "zrangestore",
+            "dst",
+            "zset",
+            "3",
+            "(2",
+            "byscore",
+            "rev",
+        ]))?
+        .execute(&backend);
+        assert_eq!(reply, 2.into());
+        assert_eq!(backend.zscore("dst", "c")?, Some(2.5));
+        assert_eq!(backend.zcard("dst")?, 2);
+        // an empty range deletes the destination
+        let reply = ZRangeStore::try_from(command(&["zrangestore", "dst", "none", "0", "-1"]))?
+            .execute(&backend);
+        assert_eq!(reply, 0.into());
+        assert_eq!(backend.key_type("dst"), None);
+
+        let backend = Backend::default();
+        zadd(&backend, "", &[(0.0, "a"), (0.0, "b"), (0.0, "c")]);
+        let reply = ZRangeByLex::try_from(command(&[
+            "zrangebylex",
+            "zset",
+            "(a",
+            "+",
+            "limit",
+            "0",
+            "1",
+        ]))?
+        .execute(&backend);
+        assert_eq!(reply, RespArray(vec![bulk("b")]).into());
+        let reply =
+            ZLexCount::try_from(command(&["zlexcount", "zset", "-", "[b"]))?.execute(&backend);
+        assert_eq!(reply, 2.into());
+
+        backend.set("string".to_string(), bulk("v"));
+        let reply = ZRange::try_from(command(&["zrange", "string", "0", "-1"]))?.execute(&backend);
+        assert!(matches!(reply, RespFrame::Error(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zset_algebra_from_resp_array() -> Result<()> {
+        let cmd = ZUnionStore::try_from(command(&[
+            "zunionstore",
+            "dst",
+            "2",
+            "a",
+            "b",
+            "weights",
+            "2",
+            "-inf",
+            "AGGREGATE",
+            "max",
+        ]))?;
+        assert_eq!(cmd.destination, "dst");
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert_eq!(
+            cmd.options,
+            ZCombineOptions {
+                weights: vec![2.0, f64::NEG_INFINITY],
+                aggregate: Aggregate::Max,
+            }
+        );
+        let cmd = ZDiff::try_from(command(&["zdiff", "1", "a", "withscores"]))?;
+        assert!(cmd.with_scores);
+
+        let cases: [(&[&str], &str); 7] = [
+            (
+                &["zunionstore", "dst", "0", "a"],
+                "at least 1 input key is needed for 'zunionstore' command",
+            ),
+            (&["zunionstore", "dst", "3", "a", "b"], "syntax error"),
+            (
+                &["zunionstore", "dst", "2", "a", "b", "weights", "1"],
+                "syntax error",
+            ),
+            (
+                &["zunionstore", "dst", "1", "a", "weights", "x"],
+                "weight value is not a float",
+            ),
+            (
+                &["zunionstore", "dst", "1", "a", "aggregate", "avg"],
+                "syntax error",
+            ),
+            (
+                &["zunionstore", "dst", "1", "a", "withscores"],
+                "syntax error",
+            ),
+            (
+                &["zdiffstore", "dst", "1", "a", "weights", "1"],
+                "syntax error",
+            ),
+        ];
+        for (case, message) in cases {
+            let err = match case[0] {
+                "zunionstore" => ZUnionStore::try_from(command(case)).map(|_| ()),
+                _ => ZDiffStore::try_from(command(case)).map(|_| ()),
+            }
+            .unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zset_algebra() -> Result<()> {
+        let backend = Backend::default();
+        let add = |key: &str, pairs: &[(f64, &str)]| {
+            let pairs = pairs.iter().map(|(s, m)| (*s, m.to_string())).collect();
+            backend.zadd(key.to_string(), pairs, ZAddOptions::default())
+        };
+        add(
+            "mon",
+            &[(1.0, "alice"), (2.0, "bob"), (f64::INFINITY, "carol")],
+        )?;
+        add(
+            "tue",
+            &[(3.0, "bob"), (f64::NEG_INFINITY, "carol"), (4.0, "dave")],
+        )?;
+        backend.sadd(
+            "set".to_string(),
+            vec!["alice".to_string(), "bob".to_string()],
+        )?;
+
+        let run = |args: &[&str]| -> Result<RespFrame> {
+            Ok(match args[0] {
+                "zunion" => ZUnion::try_from(command(args))?.execute(&backend),
+                "zinter" => ZInter::try_from(command(args))?.execute(&backend),
+                "zdiff" => ZDiff::try_from(command(args))?.execute(&backend),
+                "zunionstore" => ZUnionStore::try_from(command(args))?.execute(&backend),
+                "zinterstore" => ZInterStore::try_from(command(args))?.execute(&backend),
+                _ => ZDiffStore::try_from(command(args))?.execute(&backend),
+            })
+        };
+        let reply = |items: &[(&str, f64)]| -> RespFrame {
+            let mut reply = vec![];
+            for (member, score) in items {
+                reply.push(bulk(member));
+                reply.push(RespFrame::Double(*score));
+            }
+            RespArray(reply).into()
+        };
+
+        // inf + -inf sums to 0
+        assert_eq!(
+            run(&["zunion", "2", "mon", "tue", "withscores"])?,
+            reply(&[("carol", 0.0), ("alice", 1.0), ("dave", 4.0), ("bob", 5.0)])
+        );
+        assert_eq!(
+            run(&[
+                "zinter",
+                "2",
+                "mon",
+                "tue",
+                "aggregate",
+                "min",
+                "withscores"
+            ])?,
+            reply(&[("carol", f64::NEG_INFINITY), ("bob", 2.0)])
+        );
+        // the members of plain sets have a score of 1
+        assert_eq!(
+            run(&[
+                "zinter",
+                "2",
+                "mon",
+                "set",
+                "weights",
+                "0",
+                "10",
+                "withscores"
+            ])?,
+            reply(&[("alice", 10.0), ("bob", 10.0)])
+        );
+        assert_eq!(
+            run(&["zdiff", "2", "mon", "set", "withscores"])?,
+            reply(&[("carol", f64::INFINITY)])
+        );
+        assert_eq!(
+            run(&["zdiff", "2", "none", "mon"])?,
+            RespArray(vec![]).into()
+        );
+        assert_eq!(
+            run(&["zinter", "2", "mon", "none"])?,
+            RespArray(vec![]).into()
+        );
+
+        assert_eq!(
+            run(&[
+                "zunionstore",
+                "week",
+                "2",
+                "mon",
+                "tue",
+                "weights",
+                "1",
+                "2"
+            ])?,
+            4.into()
+        );
+        assert_eq!(backend.zscore("week", "bob")?, Some(8.0));
+        assert_eq!(backend.zscore("week", "carol")?, Some(0.0));
+        // a destination that is also an input is read before being replaced
+        assert_eq!(
+            run(&[
+                "zinterstore",
+                "week",
+                "2",
+                "week",
+                "mon",
+                "aggregate",
+                "max"
+            ])?,
+            3.into()
+        );
+        assert_eq!(backend.zscore("week", "bob")?, Some(8.0));
+        assert_eq!(backend.zscore("week", "dave")?, None);
+        assert_eq!(run(&["zdiffstore", "week", "2", "mon", "week"])?, 0.into());
+        assert_eq!(backend.key_type("week"), None);
+
+        backend.set("string".to_string(), bulk("v"));
+        assert!(matches!(
+            run(&["zunion", "2", "mon", "string"])?,
+            RespFrame::Error(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zpop_commands() -> Result<()> {
+        let backend = Backend::default();
+        zadd(
+            &backend,
+            "",
+            &[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d")],
+        );
+
+        let cmd = ZPopMin::try_from(command(&["zpopmin", "zset"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![bulk("a"), RespFrame::Double(1.0)]).into()
+        );
+        let cmd = ZPopMax::try_from(command(&["zpopmax", "zset", "2"]))?;
+        assert_eq!(
+            Protocol::Resp2.reply(cmd.execute(&backend)),
+            RespArray(vec![bulk("d"), bulk("4"), bulk("c"), bulk("3")]).into()
+        );
+        let cmd = ZPopMin::try_from(command(&["zpopmin", "none"]))?;
+        assert_eq!(cmd.execute(&backend), RespArray(vec![]).into());
+        let err = ZPopMin::try_from(command(&["zpopmin", "zset", "-1"])).unwrap_err();
+        assert_eq!(err.to_string(), "value is out of range, must be positive");
+
+        zadd(&backend, "", &[(5.0, "e")]);
+        let cmd = ZMPop::try_from(command(&[
+            "zmpop", "2", "none", "zset", "MAX", "count", "5",
+        ]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray(vec![
+                bulk("zset"),
+                RespArray(vec![
+                    RespArray(vec![bulk("e"), RespFrame::Double(5.0)]).into(),
+                    RespArray(vec![bulk("b"), RespFrame::Double(2.0)]).into(),
+                ])
+                .into(),
+            ])
+            .into()
+        );
+        assert_eq!(backend.key_type("zset"), None);
+        let cmd = ZMPop::try_from(command(&["zmpop", "1", "zset", "min"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cases: [(&[&str], &str); 3] = [
+            (
+                &["zmpop", "0", "z", "min"],
+                "numkeys should be greater than 0",
+            ),
+            (&["zmpop", "1", "z", "left"], "syntax error"),
+            (
+                &["zmpop", "1", "z", "min", "count", "0"],
+                "count should be greater than 0",
+            ),
+        ];
+        for (case, message) in cases {
+            let err = ZMPop::try_from(command(case)).unwrap_err();
+            assert_eq!(err.to_string(), message, "{:?}", case);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_zpop_commands() -> Result<()> {
+        let backend = Backend::default();
+        zadd(&backend, "", &[(1.0, "a"), (2.0, "b")]);
+        let cmd = BZPopMax::try_from(command(&["bzpopmax", "none", "zset", "0"]))?;
+        assert_eq!(
+            Command::from(cmd).run(&backend, &mut Session::new()).await,
+            RespArray(vec![bulk("zset"), bulk("b"), RespFrame::Double(2.0)]).into()
+        );
+
+        let cmd = BZPopMin::try_from(command(&["bzpopmin", "other", "0.01"]))?;
+        assert_eq!(
+            Command::from(cmd).run(&backend, &mut Session::new()).await,
+            RespFrame::Null(RespNull)
+        );
+
+        // the clients blocked on a key are served in the order they blocked
+        let block = |args: &[&str]| {
+            let backend = backend.clone();
+            let cmd = Command::try_from(command(args));
+            tokio::spawn(async move { cmd.unwrap().run(&backend, &mut Session::new()).await })
+        };
+        let first = block(&["bzpopmin", "other", "0"]);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let second = block(&["bzmpop", "0", "1", "other", "max", "count", "2"]);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let cmd = ZIncrBy {
+            key: "other".to_string(),
+            increment: 3.0,
+            member: "x".to_string(),
+        };
+        cmd.execute(&backend);
+        assert_eq!(
+            first.await?,
+            RespArray(vec![bulk("other"), bulk("x"), RespFrame::Double(3.0)]).into()
+        );
+        assert!(!second.is_finished());
+
+        let pairs = vec![(1.0, "y".to_string()), (2.0, "z".to_string())];
+        backend.zadd("other".to_string(), pairs, ZAddOptions::default())?;
+        assert_eq!(
+            second.await?,
+            RespArray(vec![
+                bulk("other"),
+                RespArray(vec![
+                    RespArray(vec![bulk("z"), RespFrame::Double(2.0)]).into(),
+                    RespArray(vec![bulk("y"), RespFrame::Double(1.0)]).into(),
+                ])
+                .into(),
+            ])
+            .into()
+        );
+        assert_eq!(backend.key_type("other"), None);
+        Ok(())
+    }
+}
@@ -2,3 +2,5 @@ pub mod backend;
 mod cmd;
 pub mod network;
 mod resp;
+#[cfg(test)]
+mod test_util;
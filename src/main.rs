@@ -1,5 +1,8 @@
 use anyhow::Result;
-use simple_redis::{backend::Backend, network};
+use simple_redis::{
+    backend::{self, Backend},
+    network,
+};
 use tokio::net::TcpListener;
 use tracing::{info, warn};
 
@@ -10,6 +13,7 @@ async fn main() -> Result<()> {
     let addr = "0.0.0.0:6379";
     info!("Simple-Redis-Server is listening on {}", addr);
     let backend = Backend::default();
+    tokio::spawn(backend::run_active_expire(backend.clone()));
     let listener = TcpListener::bind(addr).await?;
     loop {
         let (stream, raddr) = listener.accept().await?;
@@ -1,7 +1,7 @@
 use crate::{
-    backend::{self, Backend},
-    cmd::{Command, CommandExecutor},
-    resp::{RespDecode, RespEncode, RespError, RespFrame},
+    backend::Backend,
+    cmd::{Command, Session},
+    resp::{RespDecode, RespEncode, RespError, RespFrame, SimpleError},
 };
 use anyhow::{Ok, Result};
 use bytes::BufMut;
@@ -9,7 +9,7 @@ use futures::SinkExt;
 use tokio::net::TcpStream;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tracing::{info, warn};
 #[derive(Debug)]
 struct RespFrameCodec;
 
@@ -24,8 +24,9 @@ struct RedisResponse {
     frame: RespFrame,
 }
 
-pub async fn stream_handler(mut stream: TcpStream, backend: Backend) -> Result<()> {
+pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
     let mut framed = Framed::new(stream, RespFrameCodec);
+    let mut session = Session::new();
     loop {
         match framed.next().await {
             Some(core::result::Result::Ok(frame)) => {
@@ -34,23 +35,51 @@ pub async fn stream_handler(mut stream: TcpStream, backend: Backend) -> Result<(
                     frame,
                     backend: backend.clone(),
                 };
-                let response = request_handler(request).await?;
+                // a blocked command is dropped, which unblocks it, if the client goes away
+                let response = tokio::select! {
+                    biased;
+                    response = request_handler(request, &mut session) => response?,
+                    _ = closed(framed.get_ref()) => return Ok(()),
+                };
                 framed.send(response.frame).await?;
             }
-            Some(Err(e)) => return Err(e.into()),
+            Some(Err(e)) => {
+                // the stream can't be resynchronized after a malformed frame,
+                // tell the client why before closing the connection
+                let frame = RespFrame::Error(SimpleError(format!("ERR Protocol error: {}", e)));
+                framed.send(frame).await?;
+                return Err(e);
+            }
             None => return Ok(()),
         }
     }
 }
 
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+async fn request_handler(request: RedisRequest, session: &mut Session) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd: Command = frame.try_into()?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
+    let frame = match Command::try_from(frame) {
+        core::result::Result::Ok(cmd) => {
+            info!("Executing command: {:?}", cmd);
+            cmd.run(&backend, session).await
+        }
+        Err(e) => {
+            warn!("Invalid command: {}", e);
+            e.into()
+        }
+    };
+    // replies are built with RESP3 types, a RESP2 client gets them downgraded
+    let frame = session.protocol().reply(frame);
     Ok(RedisResponse { frame })
 }
 
+// resolves once the client closed the connection, stays pending if it sends more commands
+async fn closed(stream: &TcpStream) {
+    let mut buf = [0; 1];
+    if let core::result::Result::Ok(1..) = stream.peek(&mut buf).await {
+        std::future::pending::<()>().await;
+    }
+}
+
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
     fn encode(
@@ -79,3 +108,129 @@ impl Decoder for RespFrameCodec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{
+        BulkString, RespArray, RespNull, RespNullArray, RespNullBulkString, RespSet,
+    };
+
+    use super::*;
+
+    fn request(args: &[&str], backend: &Backend) -> RedisRequest {
+        let frame = RespArray(
+            args.iter()
+                .map(|arg| BulkString(arg.as_bytes().to_vec()).into())
+                .collect(),
+        );
+        RedisRequest {
+            frame: frame.into(),
+            backend: backend.clone(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_handler_replies_error() -> Result<()> {
+        let backend = Backend::default();
+        let mut session = Session::new();
+        let response = request_handler(request(&["get"], &backend), &mut session).await?;
+        assert_eq!(
+            response.frame,
+            RespFrame::Error(SimpleError(
+                "ERR wrong number of arguments for 'get' command".to_string()
+            ))
+        );
+
+        let response = request_handler(
+            request(&["set", "k", "v", "xx", "nx"], &backend),
+            &mut session,
+        )
+        .await?;
+        assert_eq!(
+            response.frame,
+            RespFrame::Error(SimpleError("ERR syntax error".to_string()))
+        );
+
+        let response =
+            request_handler(request(&["expire", "k", "ten"], &backend), &mut session).await?;
+        assert_eq!(
+            response.frame,
+            RespFrame::Error(SimpleError(
+                "ERR value is not an integer or out of range".to_string()
+            ))
+        );
+
+        let response = request_handler(
+            RedisRequest {
+                frame: RespFrame::Integer(1),
+                backend: backend.clone(),
+            },
+            &mut session,
+        )
+        .await?;
+        assert!(matches!(response.frame, RespFrame::Error(_)));
+        Ok(())
+    }
+
+    async fn reply(args: &[&str], backend: &Backend, session: &mut Session) -> Result<RespFrame> {
+        Ok(request_handler(request(args, backend), session)
+            .await?
+            .frame)
+    }
+
+    #[tokio::test]
+    async fn test_request_handler_follows_protocol() -> Result<()> {
+        let backend = Backend::default();
+        let mut session = Session::new();
+        reply(&["sadd", "set", "a"], &backend, &mut session).await?;
+        assert_eq!(
+            reply(&["smembers", "set"], &backend, &mut session).await?,
+            RespArray(vec![BulkString("a".into()).into()]).into()
+        );
+        assert_eq!(
+            reply(&["get", "nosuchkey"], &backend, &mut session).await?,
+            RespNullBulkString.into()
+        );
+        assert_eq!(
+            reply(&["blpop", "nosuchkey", "0.01"], &backend, &mut session).await?,
+            RespNullArray.into()
+        );
+
+        assert!(matches!(
+            reply(&["hello", "3"], &backend, &mut session).await?,
+            RespFrame::Map(_)
+        ));
+        assert_eq!(
+            reply(&["smembers", "set"], &backend, &mut session).await?,
+            RespSet(vec![BulkString("a".into()).into()]).into()
+        );
+        assert_eq!(
+            reply(&["get", "nosuchkey"], &backend, &mut session).await?,
+            RespNull.into()
+        );
+        assert_eq!(
+            reply(&["blpop", "nosuchkey", "0.01"], &backend, &mut session).await?,
+            RespNull.into()
+        );
+
+        // the reply to HELLO 2 is already in RESP2
+        assert!(matches!(
+            reply(&["hello", "2"], &backend, &mut session).await?,
+            RespFrame::Array(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_waits_for_partial_frame() -> Result<()> {
+        let mut codec = RespFrameCodec;
+        let mut buf = bytes::BytesMut::from("*2\r\n$3\r\nget\r\n$5\r\nhel");
+        assert!(codec.decode(&mut buf)?.is_none());
+        buf.extend_from_slice(b"lo\r\n");
+        assert!(codec.decode(&mut buf)?.is_some());
+
+        let mut buf = bytes::BytesMut::from("!oops\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+        Ok(())
+    }
+}
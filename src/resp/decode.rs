@@ -169,7 +169,7 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
 
             for _ in 0..len {
                 let l = RespFrame::expect_length(data)?;
-                data = &data[l..];
+                data = data.get(l..).ok_or(RespError::NotComplete)?;
                 total += l;
             }
             Ok(total)
@@ -179,11 +179,11 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
             for _ in 0..len {
                 // key
                 let l = SimpleString::expect_length(data)?;
-                data = &data[l..];
+                data = data.get(l..).ok_or(RespError::NotComplete)?;
                 total += l;
                 // value
                 let l = RespFrame::expect_length(data)?;
-                data = &data[l..];
+                data = data.get(l..).ok_or(RespError::NotComplete)?;
                 total += l;
             }
             Ok(total)
@@ -256,7 +256,7 @@ impl RespDecode for RespNullBulkString {
         extract_fixed_data(buf, "$-1\r\n", "NullBulkString")?;
         Ok(RespNullBulkString)
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
         Ok(5)
     }
 }
@@ -268,7 +268,7 @@ impl RespDecode for RespNull {
         extract_fixed_data(buf, "_\r\n", "Null")?;
         Ok(RespNull)
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
         Ok(3)
     }
 }
@@ -280,7 +280,7 @@ impl RespDecode for RespNullArray {
         extract_fixed_data(buf, "*-1\r\n", "NullArray")?;
         Ok(RespNullArray)
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
         Ok(5)
     }
 }
@@ -362,7 +362,7 @@ impl RespDecode for bool {
             },
         }
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
         Ok(4)
     }
 }
@@ -557,11 +557,11 @@ mod tests {
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b"#t\r\n");
         let ss = bool::decode(&mut buf)?;
-        assert_eq!(ss, true);
+        assert!(ss);
 
         buf.extend_from_slice(b"#f\r\n");
         let ss = bool::decode(&mut buf)?;
-        assert_eq!(ss, false);
+        assert!(!ss);
         Ok(())
     }
 
@@ -619,7 +619,7 @@ mod tests {
         map.insert("foo".into(), (-123456.789).into());
 
         let frame = RespMap::decode(&mut buf)?;
-        assert_eq!(frame, map.into());
+        assert_eq!(frame, map);
         Ok(())
     }
 
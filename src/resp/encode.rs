@@ -120,13 +120,18 @@ impl RespEncode for bool {
 // - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 impl RespEncode for f64 {
     fn encode(self) -> Vec<u8> {
-        let res = if self.abs() > 1e+8 || self.abs() < 1e-8 {
-            format!(",{:+e}\r\n", self)
-        } else {
-            let sign = if self < 0.0 { "-" } else { "+" };
-            format!(",{}{}\r\n", sign, self.abs())
-        };
-        res.into_bytes()
+        let res = format_double(self);
+        let sign = if res.starts_with('-') { "" } else { "+" };
+        format!(",{}{}\r\n", sign, res).into_bytes()
+    }
+}
+
+/// The textual form of a double, as used in its RESP encoding (without the leading '+').
+pub fn format_double(value: f64) -> String {
+    if value.abs() > 1e+8 || value.abs() < 1e-8 {
+        format!("{:e}", value)
+    } else {
+        format!("{}", value)
     }
 }
 
@@ -250,10 +255,11 @@ mod tests {
 
         let frame: RespFrame = map.into();
         // println!("{:?}", String::from_utf8_lossy(&frame.encode()));
-        assert_eq!(
-            frame.encode(),
-            // b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n,-123456.789\r\n"
-            b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
+        // the entries come out in the iteration order of the HashMap
+        let encoded = frame.encode();
+        assert!(
+            encoded == b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n,-123456.789\r\n"
+                || encoded == b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
         );
     }
 
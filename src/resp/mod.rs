@@ -1,5 +1,6 @@
 mod decode;
 pub mod encode;
+mod protocol;
 
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
@@ -7,6 +8,8 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use thiserror::Error;
 
+pub use protocol::Protocol;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum RespError {
     #[error("Invalid frame: {0}")]
@@ -0,0 +1,114 @@
+use super::{
+    encode::format_double, BulkString, RespArray, RespFrame, RespNull, RespNullBulkString,
+};
+
+/// The RESP version a client speaks, chosen with HELLO.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Protocol {
+    /// What a client speaks until it sends HELLO 3.
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl Protocol {
+    pub fn version(&self) -> i64 {
+        match self {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        }
+    }
+
+    /// Adapt a reply, which commands build with RESP3 types, to this protocol. The only
+    /// RESP2 type they use is the null array of the pops, which is RESP3's null.
+    pub fn reply(&self, frame: RespFrame) -> RespFrame {
+        match self {
+            Protocol::Resp2 => frame.into_resp2(),
+            Protocol::Resp3 => match frame {
+                RespFrame::NullArray(_) => RespNull.into(),
+                frame => frame,
+            },
+        }
+    }
+}
+
+impl TryFrom<i64> for Protocol {
+    type Error = i64;
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            2 => Ok(Protocol::Resp2),
+            3 => Ok(Protocol::Resp3),
+            other => Err(other),
+        }
+    }
+}
+
+impl RespFrame {
+    /// Replace the types RESP2 lacks with their RESP2 counterparts, the way redis
+    /// replies to RESP2 clients: maps become flat arrays of keys and values, sets
+    /// become arrays, doubles bulk strings, booleans integers and nulls null bulk strings.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Array(array) => {
+                RespArray(array.0.into_iter().map(RespFrame::into_resp2).collect()).into()
+            }
+            RespFrame::Map(map) => {
+                // sorted by key so that the output is deterministic
+                let mut entries: Vec<_> = map.0.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut items = Vec::with_capacity(entries.len() * 2);
+                for (key, value) in entries {
+                    items.push(BulkString(key.into_bytes()).into());
+                    items.push(value.into_resp2());
+                }
+                RespArray(items).into()
+            }
+            RespFrame::Set(set) => {
+                RespArray(set.0.into_iter().map(RespFrame::into_resp2).collect()).into()
+            }
+            RespFrame::Double(value) => BulkString(format_double(value).into_bytes()).into(),
+            RespFrame::Boolean(value) => RespFrame::Integer(value as i64),
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            frame => frame,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        resp::{RespMap, RespNull, RespSet, SimpleString},
+        test_util::bulk,
+    };
+
+    #[test]
+    fn test_into_resp2() {
+        let mut map = RespMap(HashMap::new());
+        map.insert("b".to_string(), RespSet(vec![bulk("x"), 1.5.into()]).into());
+        map.insert("a".to_string(), RespFrame::Null(RespNull));
+        let frame: RespFrame = RespArray(vec![map.into(), true.into(), 7.into()]).into();
+
+        assert_eq!(
+            frame.clone().into_resp2(),
+            RespArray(vec![
+                RespArray(vec![
+                    bulk("a"),
+                    RespNullBulkString.into(),
+                    bulk("b"),
+                    RespArray(vec![bulk("x"), bulk("1.5")]).into(),
+                ])
+                .into(),
+                RespFrame::Integer(1),
+                RespFrame::Integer(7),
+            ])
+            .into()
+        );
+        assert_eq!(Protocol::Resp3.reply(frame.clone()), frame);
+
+        let ok: RespFrame = SimpleString("OK".into()).into();
+        assert_eq!(ok.clone().into_resp2(), ok);
+    }
+}
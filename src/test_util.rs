@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests.
+
+use crate::resp::{BulkString, RespArray, RespFrame};
+
+pub fn bulk(s: &str) -> RespFrame {
+    BulkString(s.into()).into()
+}
+
+/// A command as a client sends it, an array of bulk strings.
+pub fn command(args: &[&str]) -> RespArray {
+    RespArray(args.iter().map(|arg| bulk(arg)).collect())
+}